@@ -1,22 +1,372 @@
+use crate::collect_files::collect_files;
+use crate::config::{ConfigCache, Severity};
+use crate::directives::Directives;
 use crate::error::PrettystrictError;
-use std::fs;
+use crate::fix::apply_fixes;
+use crate::lint_rules::{Context, LintError, RuleRegistry, lint_rules};
+use crate::parse_css::{parse_css, read_css_file};
+use crate::report::{Output, OutputFormat};
+use crate::rules::check_property::{PropertyList, load_known_props, load_known_props_from};
+use crate::rules::check_value::{ValueList, load_known_values, load_known_values_from};
+use crate::source::Source;
+use crate::watch;
+use clap::Args;
 use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-#[allow(dead_code)]
-fn check(file: Option<String>, watch: bool) -> Result<(), PrettystrictError> {
-    let path = file.unwrap_or_else(|| "src/styles.css".to_string());
+#[derive(Args, Debug, Clone)]
+pub struct CheckArgs {
+    /// Files, directories or glob patterns to lint (defaults to `.`); `-`
+    /// reads a stylesheet from stdin
+    #[arg(value_name = "FILE")]
+    pub files: Vec<String>,
+
+    /// Path to resolve config for and report when linting stdin; implies
+    /// reading stdin when no files are given
+    #[arg(long, value_name = "PATH")]
+    pub stdin_filename: Option<PathBuf>,
+
+    /// Glob patterns of paths to skip, in addition to .gitignore
+    #[arg(long, value_name = "PATTERN")]
+    pub ignore: Vec<String>,
+
+    #[arg(short, long, default_value_t = false)]
+    pub watch: bool,
+
+    /// JSON file of properties and at-rules to add to the built-in list
+    #[arg(long, value_name = "FILE")]
+    pub props: Vec<String>,
+
+    /// JSON file of value rules merged over the built-in ones
+    #[arg(long, value_name = "FILE")]
+    pub values: Vec<String>,
+
+    /// Config file to use instead of searching for prettystrict.toml or
+    /// .prettystrictrc.json next to each linted file
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// How to print diagnostics
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Write the report to this file and print the text report to stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output_file: Option<PathBuf>,
+
+    /// Fail when more than this many warnings are reported; by default
+    /// only errors fail the run
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+
+    /// Report prettystrict-disable comments that did not disable anything
+    #[arg(long, default_value_t = false)]
+    pub report_unused_disables: bool,
+
+    /// Apply safe automatic fixes to the files, then report what is left
+    #[arg(long, default_value_t = false, conflicts_with_all = ["watch", "fix_dry_run"])]
+    pub fix: bool,
+
+    /// Show the fixes --fix would apply without writing any file
+    #[arg(long, default_value_t = false, conflicts_with = "watch")]
+    pub fix_dry_run: bool,
+}
+
+/// How often fixing re-lints and applies fixes to a file, since one round of
+/// fixes can leave overlapping ones for the next.
+const MAX_FIX_PASSES: usize = 10;
+
+/// A fix that was applied to a stylesheet.
+pub struct AppliedFix {
+    pub rule: String,
+    /// The line the fixed diagnostic was on, in the text the fix applied to.
+    pub line: usize,
+    pub description: String,
+}
+
+/// The known CSS data, rules and configs shared by every file in a run.
+pub struct Linter {
+    pub known_props: PropertyList,
+    pub known_values: ValueList,
+    /// Whether disable comments that disabled nothing are reported.
+    pub report_unused_disables: bool,
+    registry: Rc<RuleRegistry>,
+    configs: ConfigCache,
+}
+
+impl Linter {
+    /// `config` names a config file that applies to every file; without it
+    /// each file uses the nearest one found above it.
+    pub fn new(
+        known_props: PropertyList,
+        known_values: ValueList,
+        registry: RuleRegistry,
+        config: Option<&str>,
+    ) -> Result<Linter, LintError> {
+        let registry = Rc::new(registry);
+        Ok(Linter {
+            known_props,
+            known_values,
+            report_unused_disables: false,
+            configs: ConfigCache::new(config, Rc::clone(&registry))?,
+            registry,
+        })
+    }
+
+    pub fn registry(&self) -> &RuleRegistry {
+        &self.registry
+    }
+
+    /// The config files in use so far, which a watcher must observe.
+    pub fn config_files(&self) -> Vec<PathBuf> {
+        self.configs.files()
+    }
+
+    /// Drops the loaded configs so edits to them apply to the next check.
+    pub fn reload_configs(&mut self) {
+        self.configs.clear();
+    }
+
+    pub fn check_path(&mut self, path: &Path) -> FileReport {
+        match read_css_file(&path.to_string_lossy()) {
+            Ok(source) => self.check_source(path, source),
+            Err(error) => FileReport {
+                path: path.to_path_buf(),
+                source: None,
+                errors: vec![error],
+            },
+        }
+    }
+
+    /// Fixes the file at `path`, writing the result back unless `dry_run`,
+    /// and reports the problems left in the fixed text.
+    pub fn fix_path(&mut self, path: &Path, dry_run: bool) -> (FileReport, Vec<AppliedFix>) {
+        let source = match read_css_file(&path.to_string_lossy()) {
+            Ok(source) => source,
+            Err(error) => {
+                let report = FileReport {
+                    path: path.to_path_buf(),
+                    source: None,
+                    errors: vec![error],
+                };
+                return (report, Vec::new());
+            }
+        };
+
+        let (fixed, applied) = self.fix_source(path, source.clone());
+        if !dry_run
+            && fixed != source
+            && let Err(e) = std::fs::write(path, &fixed)
+        {
+            let error = LintError {
+                message: format!("Failed to write fixed CSS file: {}", e),
+                ..LintError::from(PrettystrictError::IoError(e))
+            };
+            return (self.check_source(path, source).with_error(error), Vec::new());
+        }
+
+        (self.check_source(path, fixed), applied)
+    }
+
+    /// Applies the fixes attached to the diagnostics for `source`, linting
+    /// again after each round until nothing more can be fixed.
+    pub fn fix_source(&mut self, path: &Path, mut source: String) -> (String, Vec<AppliedFix>) {
+        let mut applied = Vec::new();
+        for _ in 0..MAX_FIX_PASSES {
+            let Ok(errors) = self.lint_source(path, &source) else {
+                break;
+            };
+            let (fixed, fixed_errors) = apply_fixes(&source, &errors);
+            if fixed_errors.is_empty() {
+                break;
+            }
+            applied.extend(fixed_errors.into_iter().map(|error| AppliedFix {
+                rule: error.rule.clone(),
+                line: error.span.start.line,
+                description: error.fix.clone().unwrap_or_else(|| error.message.clone()),
+            }));
+            source = fixed;
+        }
+        (source, applied)
+    }
+
+    pub fn check_source(&mut self, path: &Path, source: String) -> FileReport {
+        let errors = match self.lint_source(path, &source) {
+            Ok(errors) => errors,
+            Err(error) => vec![error],
+        };
+
+        FileReport {
+            path: path.to_path_buf(),
+            source: Some(source),
+            errors,
+        }
+    }
+
+    fn lint_source(&mut self, path: &Path, source: &str) -> Result<Vec<LintError>, LintError> {
+        let settings = self.configs.rules_for(path)?;
+        let rules = parse_css(source)?;
+        let context = Context {
+            known_props: &self.known_props,
+            known_values: &self.known_values,
+            settings: &settings,
+            path,
+            source,
+        };
+        let errors = lint_rules(&rules, &context, &self.registry);
+        let directives = Directives::parse(&Source::new(source));
+        Ok(directives.apply(errors, self.report_unused_disables))
+    }
+}
+
+/// The diagnostics produced for a single stylesheet.
+pub struct FileReport {
+    pub path: PathBuf,
+    /// The stylesheet text, when it could be read.
+    pub source: Option<String>,
+    pub errors: Vec<LintError>,
+}
+
+impl FileReport {
+    fn with_error(mut self, error: LintError) -> FileReport {
+        self.errors.insert(0, error);
+        self
+    }
+}
+
+/// Lints every stylesheet matched by `files` and prints a grouped report,
+/// returning whether the run failed: any error-level diagnostic does, and
+/// warnings do once there are more than `--max-warnings`.
+pub fn check(args: CheckArgs) -> Result<bool, LintError> {
+    let CheckArgs {
+        files,
+        ignore,
+        watch,
+        props,
+        values,
+        config,
+        format,
+        output_file,
+        max_warnings,
+        report_unused_disables,
+        fix,
+        fix_dry_run,
+        stdin_filename,
+    } = args;
+    let output = Output {
+        format,
+        file: output_file,
+    };
+    let read_stdin = files.iter().any(|file| file == "-")
+        || (files.is_empty() && stdin_filename.is_some());
+    let files: Vec<String> = files.into_iter().filter(|file| file != "-").collect();
+    let files = if files.is_empty() && !read_stdin {
+        vec![".".to_string()]
+    } else {
+        files
+    };
+    if read_stdin && (watch || fix) {
+        return Err(PrettystrictError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "stdin cannot be watched or fixed in place; use --fix-dry-run to preview fixes",
+        ))
+        .into());
+    }
+
+    let mut known_props = load_known_props()?;
+    for path in &props {
+        known_props.extend(load_known_props_from(path)?);
+    }
+    let mut known_values = load_known_values()?;
+    for path in &values {
+        known_values.extend(load_known_values_from(path)?);
+    }
+
+    let mut linter = Linter::new(
+        known_props,
+        known_values,
+        RuleRegistry::default(),
+        config.as_deref(),
+    )?;
+    linter.report_unused_disables = report_unused_disables;
 
     if watch {
-        println!("Watching for changes...");
-        Ok(())
+        watch::watch(&files, &ignore, &output, &mut linter)?;
+        Ok(false)
     } else {
-        println!("Checking {}", path);
-        let mut css_file = fs::File::open(&path)?;
-        let mut contents = String::new();
-        css_file.read_to_string(&mut contents)?;
+        let mut reports = Vec::new();
+        if read_stdin {
+            let path = stdin_filename.unwrap_or_else(|| PathBuf::from("<stdin>"));
+            let mut source = String::new();
+            std::io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|e| LintError {
+                    message: format!("Failed to read CSS from stdin: {}", e),
+                    ..LintError::from(PrettystrictError::IoError(e))
+                })?;
+            if fix_dry_run {
+                let (fixed, applied) = linter.fix_source(&path, source);
+                print_fixes(&path, &applied, true);
+                reports.push(linter.check_source(&path, fixed));
+            } else {
+                reports.push(linter.check_source(&path, source));
+            }
+        }
+
+        let paths = if files.is_empty() {
+            Vec::new()
+        } else {
+            collect_files(&files, &ignore)?
+        };
+        for path in paths {
+            if fix || fix_dry_run {
+                let (report, applied) = linter.fix_path(&path, fix_dry_run);
+                print_fixes(&path, &applied, fix_dry_run);
+                reports.push(report);
+            } else {
+                reports.push(linter.check_path(&path));
+            }
+        }
 
+        output.emit(&reports.iter().collect::<Vec<_>>(), linter.registry())?;
 
+        let count = |severity| {
+            reports
+                .iter()
+                .flat_map(|report| &report.errors)
+                .filter(|error| error.severity == severity)
+                .count()
+        };
+        let errors = count(Severity::Error);
+        let warnings = count(Severity::Warning);
 
-        Ok(())
+        let mut too_many_warnings = false;
+        if let Some(max) = max_warnings
+            && warnings > max
+        {
+            eprintln!(
+                "prettystrict: {} warnings found, more than the --max-warnings limit of {}",
+                warnings, max
+            );
+            too_many_warnings = true;
+        }
+
+        Ok(errors > 0 || too_many_warnings)
+    }
+}
+
+/// Lists applied fixes on stderr, keeping stdout for the report itself.
+fn print_fixes(path: &Path, applied: &[AppliedFix], dry_run: bool) {
+    if applied.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{} {}:",
+        if dry_run { "Would fix" } else { "Fixed" },
+        path.display()
+    );
+    for fix in applied {
+        eprintln!("  {:>4}  {}  {}", fix.line, fix.rule, fix.description);
     }
 }
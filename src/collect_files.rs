@@ -0,0 +1,139 @@
+use crate::error::PrettystrictError;
+use crate::lint_rules::LintError;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+pub const IGNORE_FILE: &str = ".prettystrictignore";
+
+/// Expands files, directories and glob patterns into the stylesheets to lint.
+///
+/// Directories and globs are walked honouring `.gitignore` and
+/// `.prettystrictignore`; `ignore` patterns apply to every candidate.
+/// Files named explicitly are always kept unless they match `ignore`.
+pub fn collect_files(patterns: &[String], ignore: &[String]) -> Result<Vec<PathBuf>, LintError> {
+    let ignored = build_glob_set(ignore)?;
+    let mut files = BTreeSet::new();
+
+    for pattern in patterns {
+        let path = Path::new(pattern);
+
+        if is_glob(pattern) {
+            let matcher = build_glob_set(std::slice::from_ref(pattern))?;
+            for file in walk(&glob_base(path)) {
+                if matcher.is_match(normalize(&file)) {
+                    files.insert(file);
+                }
+            }
+        } else if path.is_dir() {
+            files.extend(walk(path).into_iter().filter(|file| is_stylesheet(file)));
+        } else if path.is_file() {
+            files.insert(path.to_path_buf());
+        } else {
+            return Err(PrettystrictError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no such file or directory: {}", pattern),
+            ))
+            .into());
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .filter(|file| !ignored.is_match(normalize(file)))
+        .collect())
+}
+
+/// Like `collect_files`, but paths named explicitly that no longer exist are
+/// left out instead of failing, as a watcher sees when a file is deleted or
+/// an atomic save replaces it between events.
+pub fn collect_present_files(
+    patterns: &[String],
+    ignore: &[String],
+) -> Result<Vec<PathBuf>, LintError> {
+    let present: Vec<String> = patterns
+        .iter()
+        .filter(|pattern| is_glob(pattern) || Path::new(pattern).exists())
+        .cloned()
+        .collect();
+    collect_files(&present, ignore)
+}
+
+/// The paths a watcher must observe to see every file `patterns` can match,
+/// paired with whether they need to be watched recursively.
+pub fn watch_roots(patterns: &[String]) -> Vec<(PathBuf, bool)> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let path = Path::new(pattern);
+            if is_glob(pattern) {
+                (glob_base(path), true)
+            } else if path.is_dir() {
+                (path.to_path_buf(), true)
+            } else {
+                // Watch the parent so editors that save by renaming a
+                // temporary file over the original are still noticed.
+                match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => (parent.to_path_buf(), false),
+                    _ => (PathBuf::from("."), false),
+                }
+            }
+        })
+        .collect()
+}
+
+fn walk(root: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .add_custom_ignore_filename(IGNORE_FILE)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, LintError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern.trim_start_matches("./")).map_err(|e| {
+            LintError::from(PrettystrictError::Custom(format!(
+                "invalid glob '{}': {}",
+                pattern, e
+            )))
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| PrettystrictError::Custom(e.to_string()).into())
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+fn is_stylesheet(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("css"))
+}
+
+/// The longest leading part of a glob pattern that contains no wildcards.
+fn glob_base(pattern: &Path) -> PathBuf {
+    let base: PathBuf = pattern
+        .components()
+        .take_while(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        .collect();
+    if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    }
+}
+
+/// Strips a leading `./` so walked paths line up with user-written globs.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
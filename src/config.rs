@@ -0,0 +1,461 @@
+use crate::error::PrettystrictError;
+use crate::format::{FormatOptions, QuoteStyle};
+use crate::lint_rules::{LintError, RuleRegistry};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// File names searched for, in order, in each directory above a linted file.
+pub const CONFIG_FILES: [&str; 2] = ["prettystrict.toml", ".prettystrictrc.json"];
+
+/// Presets that can be named in `extends` instead of a path.
+const PRESETS: [(&str, &str); 2] = [
+    ("prettystrict:recommended", include_str!("presets/recommended.toml")),
+    ("prettystrict:strict", include_str!("presets/strict.toml")),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Off,
+}
+
+/// A rule entry as written in a config file: either just a severity, or a
+/// table with an optional `severity` and any rule-specific options.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum RuleSetting {
+    Severity(Severity),
+    Table {
+        #[serde(default)]
+        severity: Option<Severity>,
+        #[serde(flatten)]
+        options: Map<String, Value>,
+    },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    extends: Vec<String>,
+    rules: BTreeMap<String, RuleSetting>,
+    overrides: Vec<RawOverride>,
+    format: FormatConfig,
+}
+
+/// The `[format]` table; unset entries fall back to the formatter defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FormatConfig {
+    pub indent_width: Option<usize>,
+    pub quote_style: Option<QuoteStyle>,
+    pub max_line_length: Option<usize>,
+}
+
+impl FormatConfig {
+    /// Overrides the settings that `other` sets.
+    fn apply(&mut self, other: &FormatConfig) {
+        if other.indent_width.is_some() {
+            self.indent_width = other.indent_width;
+        }
+        if other.quote_style.is_some() {
+            self.quote_style = other.quote_style;
+        }
+        if other.max_line_length.is_some() {
+            self.max_line_length = other.max_line_length;
+        }
+    }
+
+    pub fn options(&self) -> FormatOptions {
+        let defaults = FormatOptions::default();
+        FormatOptions {
+            indent_width: self.indent_width.unwrap_or(defaults.indent_width),
+            quote_style: self.quote_style.unwrap_or(defaults.quote_style),
+            max_line_length: self.max_line_length.unwrap_or(defaults.max_line_length),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawOverride {
+    files: Vec<String>,
+    #[serde(default)]
+    rules: BTreeMap<String, RuleSetting>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RuleConfig {
+    /// Unset when only options were configured, leaving the rule's default.
+    pub severity: Option<Severity>,
+    pub options: Map<String, Value>,
+}
+
+impl RuleConfig {
+    fn apply(&mut self, setting: &RuleSetting) {
+        match setting {
+            RuleSetting::Severity(severity) => self.severity = Some(*severity),
+            RuleSetting::Table { severity, options } => {
+                if severity.is_some() {
+                    self.severity = *severity;
+                }
+                self.options
+                    .extend(options.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Override {
+    root: PathBuf,
+    files: GlobSet,
+    rules: BTreeMap<String, RuleSetting>,
+}
+
+/// A fully resolved config file, with its `extends` chain already applied.
+#[derive(Debug, Default)]
+pub struct Config {
+    rules: BTreeMap<String, RuleConfig>,
+    overrides: Vec<Override>,
+    pub format: FormatConfig,
+    /// The config file and every file it extends, presets aside.
+    files: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Loads the config at `path`, validating rule ids and options against
+    /// `registry`.
+    pub fn load(path: &Path, registry: &RuleRegistry) -> Result<Config, LintError> {
+        let path = std::path::absolute(path).map_err(PrettystrictError::from)?;
+        let mut stack = Vec::new();
+        load_config(&path, registry, &mut stack)
+    }
+
+    /// The rule settings in effect for `path`, after matching overrides.
+    pub fn rules_for(&self, path: &Path) -> RuleSet {
+        let mut rules = self.rules.clone();
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+        for override_ in &self.overrides {
+            let relative = path.strip_prefix(&override_.root).unwrap_or(&path);
+            if override_.files.is_match(relative) {
+                for (id, setting) in &override_.rules {
+                    rules.entry(id.clone()).or_default().apply(setting);
+                }
+            }
+        }
+
+        RuleSet { rules }
+    }
+
+    /// Applies an extended config over the ones merged before it, so later
+    /// `extends` entries win over earlier ones; the file's own settings are
+    /// applied after every base.
+    fn merge(&mut self, base: Config) {
+        for (id, rule) in base.rules {
+            let entry = self.rules.entry(id).or_default();
+            if rule.severity.is_some() {
+                entry.severity = rule.severity;
+            }
+            entry.options.extend(rule.options);
+        }
+        self.overrides.extend(base.overrides);
+        self.format.apply(&base.format);
+        self.files.extend(base.files);
+    }
+
+    fn apply(
+        &mut self,
+        raw: RawConfig,
+        root: &Path,
+        source: &Path,
+        registry: &RuleRegistry,
+    ) -> Result<(), LintError> {
+        self.format.apply(&raw.format);
+        for (id, setting) in &raw.rules {
+            check_rule(id, setting, source, registry)?;
+            self.rules.entry(id.clone()).or_default().apply(setting);
+        }
+
+        for raw_override in raw.overrides {
+            for (id, setting) in &raw_override.rules {
+                check_rule(id, setting, source, registry)?;
+            }
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &raw_override.files {
+                let glob = Glob::new(pattern.trim_start_matches("./")).map_err(|e| {
+                    config_error(source, format!("invalid glob '{}': {}", pattern, e))
+                })?;
+                builder.add(glob);
+            }
+            self.overrides.push(Override {
+                root: root.to_path_buf(),
+                files: builder
+                    .build()
+                    .map_err(|e| config_error(source, e.to_string()))?,
+                rules: raw_override.rules,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// The effective per-rule settings for one file.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: BTreeMap<String, RuleConfig>,
+}
+
+impl RuleSet {
+    /// The configured severity of rule `id`, if any config sets one.
+    pub fn severity(&self, id: &str) -> Option<Severity> {
+        self.rules.get(id)?.severity
+    }
+
+    /// Reads option `key` of rule `id`; options are validated on load.
+    pub fn option<T: DeserializeOwned>(&self, id: &str, key: &str) -> Option<T> {
+        let value = self.rules.get(id)?.options.get(key)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+}
+
+/// Finds and caches the config that applies to each linted file.
+pub struct ConfigCache {
+    registry: Rc<RuleRegistry>,
+    explicit: Option<PathBuf>,
+    loaded: HashMap<PathBuf, Rc<Config>>,
+    fallback: Rc<Config>,
+}
+
+impl ConfigCache {
+    /// When `explicit` is given, that config applies to every file and no
+    /// discovery happens.
+    pub fn new(
+        explicit: Option<&str>,
+        registry: Rc<RuleRegistry>,
+    ) -> Result<ConfigCache, LintError> {
+        let mut loaded = HashMap::new();
+        let explicit = match explicit {
+            Some(path) => {
+                let path = std::path::absolute(path).map_err(PrettystrictError::from)?;
+                let config = Config::load(&path, &registry)?;
+                loaded.insert(path.clone(), Rc::new(config));
+                Some(path)
+            }
+            None => None,
+        };
+        Ok(ConfigCache {
+            registry,
+            explicit,
+            loaded,
+            fallback: Rc::new(Config::default()),
+        })
+    }
+
+    /// Forgets every loaded config, so the next lookups read them again.
+    pub fn clear(&mut self) {
+        self.loaded.clear();
+    }
+
+    /// The config files loaded so far, including the files they extend.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.explicit.iter().cloned().collect();
+        for config in self.loaded.values() {
+            files.extend(config.files.iter().cloned());
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    pub fn rules_for(&mut self, file: &Path) -> Result<RuleSet, LintError> {
+        Ok(self.config_for(file)?.rules_for(file))
+    }
+
+    pub fn format_for(&mut self, file: &Path) -> Result<FormatOptions, LintError> {
+        Ok(self.config_for(file)?.format.options())
+    }
+
+    fn config_for(&mut self, file: &Path) -> Result<Rc<Config>, LintError> {
+        let Some(path) = self.explicit.clone().or_else(|| find_config(file)) else {
+            return Ok(Rc::clone(&self.fallback));
+        };
+        if let Some(config) = self.loaded.get(&path) {
+            return Ok(Rc::clone(config));
+        }
+
+        let config = Rc::new(Config::load(&path, &self.registry)?);
+        self.loaded.insert(path, Rc::clone(&config));
+        Ok(config)
+    }
+}
+
+/// Walks up from the directory containing `file` to the nearest config file.
+pub fn find_config(file: &Path) -> Option<PathBuf> {
+    let file = std::path::absolute(file).ok()?;
+    file.ancestors().skip(1).find_map(|dir| {
+        CONFIG_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+fn load_config(
+    path: &Path,
+    registry: &RuleRegistry,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Config, LintError> {
+    if stack.iter().any(|seen| seen == path) {
+        return Err(config_error(path, "circular `extends`".to_string()));
+    }
+    stack.push(path.to_path_buf());
+
+    let contents = fs::read_to_string(path).map_err(|e| config_error(path, e.to_string()))?;
+    let raw = parse_raw(&contents, path)?;
+    let root = path.parent().unwrap_or(Path::new("."));
+
+    let mut config = Config::default();
+    for base in &raw.extends {
+        let base_config = match PRESETS.iter().find(|(name, _)| name == base) {
+            Some((name, preset)) => {
+                let mut preset_config = Config::default();
+                let preset_raw = parse_raw(preset, Path::new(name))?;
+                preset_config.apply(preset_raw, root, Path::new(name), registry)?;
+                preset_config
+            }
+            None => load_config(&root.join(base), registry, stack)?,
+        };
+        config.merge(base_config);
+    }
+    config.apply(raw, root, path, registry)?;
+    config.files.push(path.to_path_buf());
+
+    stack.pop();
+    Ok(config)
+}
+
+fn parse_raw(contents: &str, path: &Path) -> Result<RawConfig, LintError> {
+    let is_json = path.extension().is_some_and(|ext| ext == "json");
+    let raw = if is_json {
+        serde_json::from_str(contents).map_err(|e| config_error(path, e.to_string()))?
+    } else {
+        toml::from_str(contents).map_err(|e| config_error(path, e.to_string()))?
+    };
+    Ok(raw)
+}
+
+fn check_rule(
+    id: &str,
+    setting: &RuleSetting,
+    source: &Path,
+    registry: &RuleRegistry,
+) -> Result<(), LintError> {
+    let Some(rule) = registry.get(id) else {
+        return Err(config_error(source, format!("unknown rule '{}'", id)));
+    };
+
+    let RuleSetting::Table { options, .. } = setting else {
+        return Ok(());
+    };
+    for (key, value) in options {
+        let Some(option) = rule.options().iter().find(|option| option.name == key) else {
+            return Err(config_error(
+                source,
+                format!("rule '{}' has no option '{}'", id, key),
+            ));
+        };
+        if !option.kind.accepts(value) {
+            return Err(config_error(
+                source,
+                format!("invalid value for option '{}' of rule '{}'", key, id),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn config_error(path: &Path, message: String) -> LintError {
+    PrettystrictError::ConfigError(format!("{}: {}", path.display(), message)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_extends_win_and_the_file_itself_wins_last() {
+        let dir = std::env::temp_dir().join(format!("prettystrict-extends-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("first.toml"),
+            "[rules]\nmax-nesting-depth = { severity = \"off\", max = 1 }\n\
+            unit-allowed = \"off\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("second.toml"),
+            "[rules]\nmax-nesting-depth = { severity = \"error\", max = 2 }\n\
+            unit-allowed = \"warning\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("prettystrict.toml"),
+            "extends = [\"first.toml\", \"second.toml\"]\n\
+            [rules]\nunit-allowed = \"info\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load(&dir.join("prettystrict.toml"), &RuleRegistry::default());
+        fs::remove_dir_all(&dir).ok();
+        let rules = config.unwrap().rules_for(&dir.join("a.css"));
+
+        assert_eq!(rules.severity("max-nesting-depth"), Some(Severity::Error));
+        assert_eq!(rules.option::<usize>("max-nesting-depth", "max"), Some(2));
+        assert_eq!(rules.severity("unit-allowed"), Some(Severity::Info));
+    }
+
+    #[test]
+    fn clearing_the_cache_rereads_edited_configs() {
+        let dir = std::env::temp_dir().join(format!("prettystrict-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("base.toml"), "[rules]\nunit-allowed = \"off\"\n").unwrap();
+        fs::write(dir.join("prettystrict.toml"), "extends = [\"base.toml\"]\n").unwrap();
+        let file = dir.join("a.css");
+
+        let mut cache = ConfigCache::new(None, Rc::new(RuleRegistry::default())).unwrap();
+        let before = cache.rules_for(&file).unwrap();
+        let files = cache.files();
+        fs::write(
+            dir.join("base.toml"),
+            "[rules]\nunit-allowed = \"warning\"\n",
+        )
+        .unwrap();
+        let cached = cache.rules_for(&file).unwrap();
+        cache.clear();
+        let after = cache.rules_for(&file);
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(
+            files,
+            [dir.join("base.toml"), dir.join("prettystrict.toml")]
+        );
+        assert_eq!(before.severity("unit-allowed"), Some(Severity::Off));
+        assert_eq!(cached.severity("unit-allowed"), Some(Severity::Off));
+        assert_eq!(
+            after.unwrap().severity("unit-allowed"),
+            Some(Severity::Warning)
+        );
+    }
+}
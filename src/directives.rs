@@ -0,0 +1,145 @@
+//handles inline prettystrict-disable / prettystrict-enable comments
+
+use crate::error::PrettystrictError;
+use crate::lint_rules::LintError;
+use crate::source::{Source, Span};
+use std::ops::Range;
+
+const DISABLE_NEXT_LINE: &str = "prettystrict-disable-next-line";
+const DISABLE: &str = "prettystrict-disable";
+const ENABLE: &str = "prettystrict-enable";
+
+/// One rule, or every rule when `rule` is `None`, switched off over the
+/// byte range `range` by the directive comment at `directive`.
+struct Suppression {
+    rule: Option<String>,
+    range: Range<usize>,
+    directive: Span,
+    used: bool,
+}
+
+impl Suppression {
+    fn covers(&self, error: &LintError) -> bool {
+        // Errors without a position, such as IO failures, cannot be disabled.
+        error.span.start.line > 0
+            && self.range.contains(&error.span.start.offset)
+            && self.rule.as_ref().is_none_or(|rule| *rule == error.rule)
+    }
+}
+
+/// The disable directives found in a stylesheet's comments.
+pub struct Directives {
+    suppressions: Vec<Suppression>,
+}
+
+impl Directives {
+    /// Collects the directives in `source`:
+    ///
+    /// - `/* prettystrict-disable-next-line [rules] */` covers the next line,
+    /// - `/* prettystrict-disable [rules] */` covers everything up to a
+    ///   matching `/* prettystrict-enable [rules] */` or the end of the file.
+    ///
+    /// Without rule ids a directive applies to every rule. Text after `--`
+    /// is a free-form reason and is ignored.
+    pub fn parse(source: &Source) -> Directives {
+        let mut suppressions = Vec::new();
+        let mut open: Vec<Suppression> = Vec::new();
+
+        for comment in source.comments() {
+            let text = source.text[comment.clone()]
+                .trim_start_matches("/*")
+                .trim_end_matches("*/");
+            let text = text.split_once("--").map_or(text, |(directive, _)| directive);
+            let mut words = text
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|word| !word.is_empty());
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            let rules: Vec<String> = words.map(str::to_string).collect();
+            let targets: Vec<Option<String>> = if rules.is_empty() {
+                vec![None]
+            } else {
+                rules.iter().cloned().map(Some).collect()
+            };
+            let directive = source.span(comment.clone());
+
+            match keyword {
+                DISABLE_NEXT_LINE => {
+                    let line = source.location(comment.end).line + 1;
+                    let range = source.line_range(line);
+                    suppressions.extend(targets.into_iter().map(|rule| Suppression {
+                        rule,
+                        range: range.clone(),
+                        directive,
+                        used: false,
+                    }));
+                }
+                DISABLE => {
+                    open.extend(targets.into_iter().map(|rule| Suppression {
+                        rule,
+                        range: comment.end..source.text.len(),
+                        directive,
+                        used: false,
+                    }));
+                }
+                ENABLE => {
+                    let (closed, still_open) = open.into_iter().partition(|suppression| {
+                        rules.is_empty()
+                            || suppression.rule.as_ref().is_some_and(|rule| rules.contains(rule))
+                    });
+                    open = still_open;
+                    suppressions.extend(closed.into_iter().map(|suppression: Suppression| {
+                        Suppression {
+                            range: suppression.range.start..comment.start,
+                            ..suppression
+                        }
+                    }));
+                }
+                _ => {}
+            }
+        }
+
+        suppressions.extend(open);
+        Directives { suppressions }
+    }
+
+    /// Drops the errors a directive disables. With `report_unused`, each
+    /// directive that disabled nothing is reported in their place.
+    pub fn apply(mut self, errors: Vec<LintError>, report_unused: bool) -> Vec<LintError> {
+        let mut kept = Vec::new();
+        for error in errors {
+            let mut suppressed = false;
+            for suppression in &mut self.suppressions {
+                if suppression.covers(&error) {
+                    suppression.used = true;
+                    suppressed = true;
+                }
+            }
+            if !suppressed {
+                kept.push(error);
+            }
+        }
+
+        if report_unused {
+            for suppression in self.suppressions.iter().filter(|s| !s.used) {
+                let message = match &suppression.rule {
+                    Some(rule) => format!(
+                        "unused prettystrict-disable directive (no '{}' problems were reported)",
+                        rule
+                    ),
+                    None => "unused prettystrict-disable directive (no problems were reported)"
+                        .to_string(),
+                };
+                kept.push(LintError {
+                    message,
+                    span: suppression.directive,
+                    fix: Some("remove the directive".to_string()),
+                    ..LintError::from(PrettystrictError::UnusedDisable)
+                });
+            }
+        }
+
+        kept
+    }
+}
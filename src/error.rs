@@ -1,14 +1,15 @@
 //provides error for this program
 
+use crate::config::Severity;
 use crate::lint_rules::LintError;
-use fmt::Display;
-use std::fmt;
+use crate::source::Span;
 use lightningcss::error::ParserError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum PrettystrictError {
     #[error("Unexpected token: {0}")]
+    #[allow(dead_code)]
     UnexpectedToken(String),
 
     #[error("Unexpected end of file")]
@@ -26,6 +27,12 @@ pub enum PrettystrictError {
     #[error("json error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("config error: {0}")]
+    ConfigError(String),
+
+    #[error("watch error: {0}")]
+    WatchError(#[from] notify_debouncer_mini::notify::Error),
+
     #[error("file error: {0}")]
     UnknownValue(String),
 
@@ -41,11 +48,95 @@ pub enum PrettystrictError {
 
     #[error("propery overridden ")]
     ProperyOverride,
+
+    #[error("property out of order")]
+    PropertyOrder,
+
+    #[error("duplicate selector")]
+    DuplicateSelector,
+
+    #[error("duplicate declaration block")]
+    DuplicateBlock,
+
+    #[error("keyframes never used: {0}")]
+    UnusedKeyframes(String),
+
+    #[error("keyframes not defined: {0}")]
+    UndefinedKeyframes(String),
+
+    #[error("declaration overridden by a later media block")]
+    MediaConflict,
+
+    #[error("unused disable directive")]
+    UnusedDisable,
+
+    #[error("rule nested too deeply")]
+    NestingDepth,
+
+    #[error("missing descriptor: {0}")]
+    MissingDescriptor(String),
+
+    #[error("font source format")]
+    FontFormat,
+
+    #[error("keyframe selector out of range: {0}")]
+    KeyframeOutOfRange(String),
+
+    #[error("duplicate keyframe offset")]
+    DuplicateKeyframe,
+
+    #[error("!important inside keyframes")]
+    KeyframeImportant,
+
+    #[error("property not animatable: {0}")]
+    NotAnimatable(String),
+
+    #[error("property missing from a keyframe endpoint: {0}")]
+    MissingEndpointProperty(String),
     #[error("invalid declaration")]
+    #[allow(dead_code)]
     InvalidDeclaration,
 
 }
 
+impl PrettystrictError {
+    /// A stable identifier for this kind of error, used as the rule id of
+    /// diagnostics that no lint rule produced.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PrettystrictError::UnexpectedToken(_) => "unexpected-token",
+            PrettystrictError::EndOfFile => "unexpected-end-of-file",
+            PrettystrictError::Custom(_) => "parse-error",
+            PrettystrictError::UnknownProperty(_) => "unknown-property",
+            PrettystrictError::IoError(_) => "io-error",
+            PrettystrictError::JsonError(_) => "json-error",
+            PrettystrictError::ConfigError(_) => "config-error",
+            PrettystrictError::WatchError(_) => "watch-error",
+            PrettystrictError::UnknownValue(_) => "unknown-value",
+            PrettystrictError::DuplicateProperty => "duplicate-property",
+            PrettystrictError::NoUnitFound => "no-unit-found",
+            PrettystrictError::WrongUnitDeclared => "wrong-unit-declared",
+            PrettystrictError::ProperyOverride => "property-override",
+            PrettystrictError::PropertyOrder => "property-order",
+            PrettystrictError::DuplicateSelector => "duplicate-selector",
+            PrettystrictError::DuplicateBlock => "duplicate-block",
+            PrettystrictError::UnusedKeyframes(_) => "unused-keyframes",
+            PrettystrictError::UndefinedKeyframes(_) => "undefined-keyframes",
+            PrettystrictError::MediaConflict => "media-conflict",
+            PrettystrictError::UnusedDisable => "unused-disable-directive",
+            PrettystrictError::NestingDepth => "nesting-depth",
+            PrettystrictError::MissingDescriptor(_) => "missing-descriptor",
+            PrettystrictError::FontFormat => "font-format",
+            PrettystrictError::KeyframeOutOfRange(_) => "keyframe-out-of-range",
+            PrettystrictError::DuplicateKeyframe => "duplicate-keyframe",
+            PrettystrictError::KeyframeImportant => "keyframe-important",
+            PrettystrictError::NotAnimatable(_) => "not-animatable",
+            PrettystrictError::MissingEndpointProperty(_) => "missing-endpoint-property",
+            PrettystrictError::InvalidDeclaration => "invalid-declaration",
+        }
+    }
+}
+
 impl From<ParserError<'_>> for PrettystrictError {
     fn from(err: ParserError) -> Self {
         match err {
@@ -106,10 +197,15 @@ impl From<ParserError<'_>> for PrettystrictError {
 impl From<PrettystrictError> for LintError {
     fn from(err: PrettystrictError) -> Self {
         LintError {
+            rule: err.code().into(),
+            severity: Severity::Error,
             selector: "".into(), // fill in or refactor later
             property: "".into(),
             message: format!("{}", err),
             kind: err,
+            span: Span::default(),
+            fix: None,
+            edits: Vec::new(),
         }
     }
 }
@@ -0,0 +1,247 @@
+//provides source edits for autofixable diagnostics
+
+use crate::lint_rules::LintError;
+use crate::rules::check_property::Property;
+use std::ops::Range;
+
+/// Replaces the bytes in `range` with `replacement`; an empty range inserts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn delete(range: Range<usize>) -> Edit {
+        Edit {
+            range,
+            replacement: String::new(),
+        }
+    }
+
+    pub fn insert(at: usize, text: String) -> Edit {
+        Edit {
+            range: at..at,
+            replacement: text,
+        }
+    }
+}
+
+/// The byte range of `property` in `source`, if its span really points at
+/// the declaration rather than at a fallback such as the rule's selector.
+fn declaration_range(source: &str, property: &Property) -> Option<Range<usize>> {
+    let range = property.span.start.offset..property.span.end.offset;
+    let text = source.get(range.clone())?;
+    let (name, _) = text.split_once(':')?;
+    name.trim()
+        .eq_ignore_ascii_case(&property.name)
+        .then_some(range)
+}
+
+/// Where the line containing `offset` starts, if only whitespace precedes
+/// `offset` on it.
+fn own_line_start(source: &str, offset: usize) -> Option<usize> {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..offset]
+        .trim()
+        .is_empty()
+        .then_some(line_start)
+}
+
+/// Deletes `property` with its `;`. A declaration alone on its line takes
+/// the whole line with it; otherwise only the spaces after it go, so
+/// comments and neighbouring declarations stay untouched.
+pub fn remove_declaration(source: &str, property: &Property) -> Option<Edit> {
+    let range = declaration_range(source, property)?;
+    let mut end = range.end;
+    let after = &source[end..];
+    let spaces = after.len() - after.trim_start_matches([' ', '\t']).len();
+    if after[spaces..].starts_with(';') {
+        end += spaces + 1;
+    }
+
+    let rest = &source[end..];
+    let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+    if let Some(line_start) = own_line_start(source, range.start)
+        && rest[..line_end].trim().is_empty()
+    {
+        return Some(Edit::delete(line_start..end + line_end));
+    }
+
+    let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    Some(Edit::delete(range.start..end + spaces))
+}
+
+/// Moves `property` so it sits directly before `before`, keeping the
+/// layout of `before`: its own line with the same indentation, or inline.
+pub fn move_declaration(source: &str, property: &Property, before: &Property) -> Option<Vec<Edit>> {
+    let moved = &source[declaration_range(source, property)?];
+    let target = declaration_range(source, before)?;
+    let removal = remove_declaration(source, property)?;
+
+    let insertion = match own_line_start(source, target.start) {
+        Some(line_start) => Edit::insert(
+            line_start,
+            format!("{}{};\n", &source[line_start..target.start], moved),
+        ),
+        None => Edit::insert(target.start, format!("{}; ", moved)),
+    };
+    Some(vec![removal, insertion])
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    // Two insertions at the same place would be applied in an arbitrary
+    // order, so they count as overlapping too.
+    a.start == b.start || (a.start < b.end && b.start < a.end)
+}
+
+/// Applies the edits of every error in `errors` that has some, skipping
+/// errors whose edits overlap ones already taken. Returns the new source
+/// and the errors that were fixed.
+pub fn apply_fixes<'a>(source: &str, errors: &'a [LintError]) -> (String, Vec<&'a LintError>) {
+    let mut fixable: Vec<&LintError> = errors.iter().filter(|e| !e.edits.is_empty()).collect();
+    fixable.sort_by_key(|error| error.edits.iter().map(|e| e.range.start).min());
+
+    let mut applied: Vec<&LintError> = Vec::new();
+    let mut edits: Vec<&Edit> = Vec::new();
+    for error in fixable {
+        let clashes = error
+            .edits
+            .iter()
+            .any(|edit| edits.iter().any(|taken| overlaps(&edit.range, &taken.range)));
+        if !clashes {
+            edits.extend(&error.edits);
+            applied.push(error);
+        }
+    }
+
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+    let mut fixed = source.to_string();
+    for edit in edits {
+        fixed.replace_range(edit.range.clone(), &edit.replacement);
+    }
+    (fixed, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PrettystrictError;
+    use crate::parse_css::parse_css;
+
+    /// Parses `source` and returns the declarations of its first rule.
+    fn declarations(source: &str) -> Vec<Property> {
+        let mut rules = parse_css(source).expect("test CSS parses");
+        rules.remove(0).declaration
+    }
+
+    fn error_with(edits: Vec<Edit>) -> LintError {
+        LintError {
+            edits,
+            ..LintError::from(PrettystrictError::DuplicateProperty)
+        }
+    }
+
+    fn apply(source: &str, edits: Vec<Edit>) -> String {
+        apply_fixes(source, &[error_with(edits)]).0
+    }
+
+    #[test]
+    fn removes_a_declaration_alone_on_its_line_with_the_line() {
+        let source = ".a {\n  color: red;\n  margin: 0;\n}\n";
+        let edit = remove_declaration(source, &declarations(source)[0]).unwrap();
+        assert_eq!(apply(source, vec![edit]), ".a {\n  margin: 0;\n}\n");
+    }
+
+    #[test]
+    fn removes_an_inline_declaration_and_the_space_after_it() {
+        let source = ".a { color: red; margin: 0; }";
+        let edit = remove_declaration(source, &declarations(source)[0]).unwrap();
+        assert_eq!(apply(source, vec![edit]), ".a { margin: 0; }");
+    }
+
+    #[test]
+    fn keeps_a_comment_on_the_same_line() {
+        let source = ".a {\n  color: red; /* brand */\n  margin: 0;\n}\n";
+        let edit = remove_declaration(source, &declarations(source)[0]).unwrap();
+        assert_eq!(
+            apply(source, vec![edit]),
+            ".a {\n  /* brand */\n  margin: 0;\n}\n"
+        );
+    }
+
+    #[test]
+    fn removes_a_last_declaration_without_semicolon() {
+        let source = ".a { margin: 0; color: red }";
+        let edit = remove_declaration(source, &declarations(source)[1]).unwrap();
+        assert_eq!(apply(source, vec![edit]), ".a { margin: 0; }");
+
+        let source = ".a {\n  margin: 0;\n  color: red\n}\n";
+        let edit = remove_declaration(source, &declarations(source)[1]).unwrap();
+        assert_eq!(apply(source, vec![edit]), ".a {\n  margin: 0;\n}\n");
+    }
+
+    #[test]
+    fn moves_a_declaration_onto_its_own_line() {
+        let source = ".a {\n  color: red;\n  display: block;\n}\n";
+        let declarations = declarations(source);
+        let edits = move_declaration(source, &declarations[1], &declarations[0]).unwrap();
+        assert_eq!(
+            apply(source, edits),
+            ".a {\n  display: block;\n  color: red;\n}\n"
+        );
+    }
+
+    #[test]
+    fn moves_an_inline_declaration() {
+        let source = ".a { color: red; display: block; }";
+        let declarations = declarations(source);
+        let edits = move_declaration(source, &declarations[1], &declarations[0]).unwrap();
+        assert_eq!(apply(source, edits), ".a { display: block; color: red; }");
+    }
+
+    #[test]
+    fn moves_a_last_declaration_without_semicolon() {
+        let source = ".a { color: red; display: block }";
+        let declarations = declarations(source);
+        let edits = move_declaration(source, &declarations[1], &declarations[0]).unwrap();
+        assert_eq!(apply(source, edits), ".a { display: block; color: red; }");
+    }
+
+    #[test]
+    fn skips_errors_whose_edits_overlap_taken_ones() {
+        let source = "abcdef";
+        let errors = [
+            error_with(vec![Edit::delete(1..3)]),
+            error_with(vec![Edit::delete(2..4)]),
+            error_with(vec![Edit::delete(4..5)]),
+        ];
+        let (fixed, applied) = apply_fixes(source, &errors);
+        assert_eq!(fixed, "adf");
+        assert_eq!(applied.len(), 2);
+        assert!(std::ptr::eq(applied[0], &errors[0]));
+        assert!(std::ptr::eq(applied[1], &errors[2]));
+    }
+
+    #[test]
+    fn treats_insertions_at_the_same_place_as_overlapping() {
+        let errors = [
+            error_with(vec![Edit::insert(1, "x".to_string())]),
+            error_with(vec![Edit::insert(1, "y".to_string())]),
+        ];
+        let (fixed, applied) = apply_fixes("ab", &errors);
+        assert_eq!(fixed, "axb");
+        assert_eq!(applied.len(), 1);
+    }
+
+    #[test]
+    fn skips_a_whole_error_when_one_of_its_edits_overlaps() {
+        let errors = [
+            error_with(vec![Edit::delete(0..1)]),
+            error_with(vec![Edit::delete(4..5), Edit::insert(0, "x".to_string())]),
+        ];
+        let (fixed, applied) = apply_fixes("abcdef", &errors);
+        assert_eq!(fixed, "bcdef");
+        assert_eq!(applied.len(), 1);
+    }
+}
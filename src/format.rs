@@ -0,0 +1,614 @@
+//provides the `format` subcommand, a comment-preserving pretty-printer
+
+use crate::collect_files::collect_files;
+use crate::config::ConfigCache;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{LintError, RuleRegistry};
+use crate::parse_css::{parse_css, read_css_file};
+use crate::source::{skip_comment, skip_string, split_top_level_commas};
+use clap::Args;
+use serde::Deserialize;
+use std::path::Path;
+use std::rc::Rc;
+
+#[derive(Args, Debug, Clone)]
+pub struct FormatArgs {
+    /// Files, directories or glob patterns to format (defaults to `.`)
+    #[arg(value_name = "FILE")]
+    pub files: Vec<String>,
+
+    /// Glob patterns of paths to skip, in addition to .gitignore
+    #[arg(long, value_name = "PATTERN")]
+    pub ignore: Vec<String>,
+
+    /// Config file to take [format] options from instead of searching for one
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// Only report files that are not formatted, without changing them
+    #[arg(long, default_value_t = false)]
+    pub check: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    #[default]
+    Double,
+    Single,
+    /// Leave quotes as written.
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions {
+    pub indent_width: usize,
+    pub quote_style: QuoteStyle,
+    /// Selector lists and comma-separated values longer than this are
+    /// broken over several lines.
+    pub max_line_length: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_width: 2,
+            quote_style: QuoteStyle::Double,
+            max_line_length: 80,
+        }
+    }
+}
+
+/// Formats every stylesheet matched by `files` in place, or with `--check`
+/// only lists the ones that would change. Returns whether the run failed.
+pub fn format(args: FormatArgs) -> Result<bool, LintError> {
+    let FormatArgs {
+        files,
+        ignore,
+        config,
+        check,
+    } = args;
+    let files = if files.is_empty() {
+        vec![".".to_string()]
+    } else {
+        files
+    };
+
+    let mut configs = ConfigCache::new(config.as_deref(), Rc::new(RuleRegistry::default()))?;
+    let mut failed = false;
+
+    for path in collect_files(&files, &ignore)? {
+        match format_path(&path, &mut configs, check) {
+            Ok(true) if check => {
+                println!("{}: not formatted", path.display());
+                failed = true;
+            }
+            Ok(true) => eprintln!("Formatted {}", path.display()),
+            Ok(false) => {}
+            Err(error) => {
+                eprintln!("{}: {}", path.display(), error.message);
+                failed = true;
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+/// Formats one file, returning whether its formatting changed (or, with
+/// `check`, would change).
+fn format_path(path: &Path, configs: &mut ConfigCache, check: bool) -> Result<bool, LintError> {
+    let source = read_css_file(&path.to_string_lossy())?;
+    // Refuse to reformat what does not parse, rather than mangle it.
+    parse_css(&source)?;
+
+    let formatted = format_css(&source, &configs.format_for(path)?);
+    if formatted == source {
+        return Ok(false);
+    }
+    if !check {
+        std::fs::write(path, formatted).map_err(|e| LintError {
+            message: format!("Failed to write formatted CSS file: {}", e),
+            ..LintError::from(PrettystrictError::IoError(e))
+        })?;
+    }
+    Ok(true)
+}
+
+/// A piece of a stylesheet, as far as layout is concerned.
+enum Node {
+    /// `trailing` comments started on the line the previous node ended on.
+    Comment { text: String, trailing: bool },
+    Declaration { name: String, value: String },
+    /// An at-rule without a block, such as `@import`.
+    Statement(String),
+    Block { prelude: String, children: Vec<Node> },
+    /// One or more empty lines in the source.
+    BlankLine,
+}
+
+/// Re-emits `source` with consistent layout. Comments are kept, and values
+/// are only respaced, never reinterpreted.
+pub fn format_css(source: &str, options: &FormatOptions) -> String {
+    let mut parser = Parser { text: source, pos: 0 };
+    let nodes = parser.parse_nodes();
+
+    let mut out = String::new();
+    Printer { options, out: &mut out }.print_nodes(&nodes, 0);
+    out
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Parses nodes until the `}` closing the current block, or the end.
+    fn parse_nodes(&mut self) -> Vec<Node> {
+        let bytes = self.text.as_bytes();
+        let mut nodes = Vec::new();
+
+        loop {
+            let start = self.pos;
+            while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            let newlines = self.text[start..self.pos].matches('\n').count();
+            if self.pos >= bytes.len() {
+                break;
+            }
+            if newlines >= 2 && !nodes.is_empty() {
+                nodes.push(Node::BlankLine);
+            }
+
+            match bytes[self.pos] {
+                b'}' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'/' if bytes.get(self.pos + 1) == Some(&b'*') => {
+                    let end = skip_comment(self.text, self.pos);
+                    nodes.push(Node::Comment {
+                        text: self.text[self.pos..end].to_string(),
+                        trailing: newlines == 0 && !nodes.is_empty(),
+                    });
+                    self.pos = end;
+                }
+                _ => {
+                    let start = self.pos;
+                    self.pos = statement_end(self.text, self.pos);
+                    let raw = &self.text[start..self.pos];
+                    match bytes.get(self.pos) {
+                        Some(b'{') => {
+                            self.pos += 1;
+                            let children = self.parse_nodes();
+                            nodes.push(Node::Block {
+                                prelude: raw.to_string(),
+                                children,
+                            });
+                        }
+                        Some(b';') => {
+                            self.pos += 1;
+                            nodes.push(statement(raw));
+                        }
+                        // A last declaration without `;`; leave the `}`
+                        // for the next round.
+                        _ => nodes.push(statement(raw)),
+                    }
+                }
+            }
+        }
+
+        nodes
+    }
+}
+
+fn statement(raw: &str) -> Node {
+    let raw = raw.trim();
+    match top_level_colon(raw) {
+        Some(colon) if !raw.starts_with('@') => Node::Declaration {
+            name: raw[..colon].trim().to_string(),
+            value: raw[colon + 1..].trim().to_string(),
+        },
+        _ => Node::Statement(raw.to_string()),
+    }
+}
+
+/// The end of the statement starting at `from`: the next top-level `;`,
+/// `{` or `}`, or the end of the text.
+fn statement_end(text: &str, from: usize) -> usize {
+    let bytes = text.as_bytes();
+    // Custom property values may contain balanced `{}` of their own.
+    let custom_property = text[from..].starts_with("--");
+    let mut i = from;
+    let mut depth = 0usize;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(text, i),
+            b'"' | b'\'' => i = skip_string(text, i),
+            b'(' | b'[' => {
+                depth += 1;
+                i += 1;
+            }
+            b'{' if custom_property && text[from..i].contains(':') => {
+                depth += 1;
+                i += 1;
+            }
+            b')' | b']' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b'}' if custom_property && depth > 0 => {
+                depth -= 1;
+                i += 1;
+            }
+            b';' | b'{' | b'}' if depth == 0 => return i,
+            _ => i += 1,
+        }
+    }
+    i.min(bytes.len())
+}
+
+fn top_level_colon(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let mut depth = 0usize;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(text, i),
+            b'"' | b'\'' => i = skip_string(text, i),
+            b'(' | b'[' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' | b']' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b':' if depth == 0 => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// What kind of text is being respaced.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Spacing {
+    Value,
+    Selector,
+    AtRulePrelude,
+}
+
+/// Collapses whitespace in `text`, normalizing the spacing around commas,
+/// parentheses and `!important`. Strings, comments, escapes and `url()`
+/// contents are copied as written, apart from the quotes of strings.
+fn respace(text: &str, spacing: Spacing, options: &FormatOptions) -> String {
+    let bytes = text.as_bytes();
+    let mut out = String::new();
+    let mut pending_space = false;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    let flush = |out: &mut String, pending_space: &mut bool| {
+        if *pending_space && !out.is_empty() && !out.ends_with(['(', '[']) {
+            out.push(' ');
+        }
+        *pending_space = false;
+    };
+
+    while i < bytes.len() {
+        let ch = bytes[i];
+        match ch {
+            c if c.is_ascii_whitespace() => {
+                pending_space = true;
+                i += 1;
+            }
+            b'\\' => {
+                // An escaped character is part of the identifier, whatever
+                // it is, so it is never respaced.
+                flush(&mut out, &mut pending_space);
+                let len = text[i + 1..].chars().next().map_or(0, char::len_utf8);
+                out.push_str(&text[i..i + 1 + len]);
+                i += 1 + len;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                flush(&mut out, &mut pending_space);
+                let end = skip_comment(text, i);
+                out.push_str(&text[i..end]);
+                i = end;
+            }
+            b'"' | b'\'' => {
+                flush(&mut out, &mut pending_space);
+                let end = skip_string(text, i);
+                out.push_str(&requote(&text[i..end], options.quote_style));
+                i = end;
+            }
+            b',' => {
+                out.push(',');
+                pending_space = true;
+                i += 1;
+            }
+            b'(' | b'[' => {
+                flush(&mut out, &mut pending_space);
+                let is_url = out.to_ascii_lowercase().ends_with("url") && ch == b'(';
+                if is_url {
+                    // Unquoted URLs may contain anything but `)`.
+                    let end = text[i..].find(')').map_or(text.len(), |e| i + e + 1);
+                    out.push_str(text[i..end].trim());
+                    i = end;
+                    continue;
+                }
+                out.push(ch as char);
+                depth += 1;
+                i += 1;
+            }
+            b')' | b']' => {
+                pending_space = false;
+                out.push(ch as char);
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b'!' if spacing == Spacing::Value => {
+                pending_space = true;
+                flush(&mut out, &mut pending_space);
+                out.push('!');
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+            }
+            b':' if spacing == Spacing::AtRulePrelude && depth > 0 => {
+                out.push(':');
+                pending_space = true;
+                i += 1;
+            }
+            b'>' | b'+' | b'~' if spacing == Spacing::Selector && depth == 0 => {
+                out.truncate(out.trim_end().len());
+                out.push(' ');
+                out.push(ch as char);
+                pending_space = true;
+                i += 1;
+            }
+            b'#' if spacing == Spacing::Value => {
+                flush(&mut out, &mut pending_space);
+                let digits = text[i + 1..]
+                    .bytes()
+                    .take_while(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_')
+                    .count();
+                let word = &text[i..i + 1 + digits];
+                if matches!(digits, 3 | 4 | 6 | 8) && word[1..].bytes().all(|b| b.is_ascii_hexdigit()) {
+                    out.push_str(&word.to_ascii_lowercase());
+                } else {
+                    out.push_str(word);
+                }
+                i += 1 + digits;
+            }
+            _ => {
+                flush(&mut out, &mut pending_space);
+                let len = text[i..].chars().next().map_or(1, char::len_utf8);
+                out.push_str(&text[i..i + len]);
+                i += len;
+            }
+        }
+    }
+
+    out
+}
+
+/// Switches a quoted string to the preferred quote, unless that would need
+/// escaping.
+fn requote(string: &str, style: QuoteStyle) -> String {
+    let quote = match style {
+        QuoteStyle::Double => '"',
+        QuoteStyle::Single => '\'',
+        QuoteStyle::Preserve => return string.to_string(),
+    };
+    let Some(first) = string.chars().next() else {
+        return string.to_string();
+    };
+    if first == quote || !string.ends_with(first) || string.len() < 2 {
+        return string.to_string();
+    }
+    let inner = &string[1..string.len() - 1];
+    if inner.contains(quote) || inner.contains('\\') {
+        return string.to_string();
+    }
+    format!("{}{}{}", quote, inner, quote)
+}
+
+struct Printer<'a> {
+    options: &'a FormatOptions,
+    out: &'a mut String,
+}
+
+impl Printer<'_> {
+    fn indent(&self, depth: usize) -> String {
+        " ".repeat(self.options.indent_width * depth)
+    }
+
+    fn print_nodes(&mut self, nodes: &[Node], depth: usize) {
+        let mut previous: Option<&Node> = None;
+        let mut blank_pending = false;
+
+        for node in nodes {
+            if let Node::BlankLine = node {
+                blank_pending = true;
+                continue;
+            }
+            if let Node::Comment {
+                text,
+                trailing: true,
+            } = node
+                && previous.is_some()
+            {
+                self.out.pop();
+                self.out.push(' ');
+                self.out.push_str(text);
+                self.out.push('\n');
+                continue;
+            }
+
+            // Top-level rules are always separated by an empty line; a
+            // comment stays attached to the rule that follows it.
+            let separate = match previous {
+                None => false,
+                Some(Node::Block { .. }) if depth == 0 => true,
+                Some(Node::Comment { .. }) => blank_pending,
+                Some(_) => blank_pending || (depth == 0 && matches!(node, Node::Block { .. })),
+            };
+            if separate {
+                self.out.push('\n');
+            }
+            blank_pending = false;
+
+            self.print_node(node, depth);
+            previous = Some(node);
+        }
+    }
+
+    fn print_node(&mut self, node: &Node, depth: usize) {
+        let indent = self.indent(depth);
+        match node {
+            Node::Comment { text, .. } => {
+                self.out.push_str(&indent);
+                self.out.push_str(text);
+                self.out.push('\n');
+            }
+            Node::Declaration { name, value } => {
+                // Custom properties are case-sensitive and may hold any
+                // tokens, so they are left as written.
+                let (name, value) = if name.starts_with("--") {
+                    (name.clone(), value.clone())
+                } else {
+                    (
+                        name.to_ascii_lowercase(),
+                        respace(value, Spacing::Value, self.options),
+                    )
+                };
+                let line = format!("{}{}: {};", indent, name, value);
+                let parts = split_top_level_commas(&value);
+                if line.len() > self.options.max_line_length && parts.len() > 1 {
+                    let inner = self.indent(depth + 1);
+                    self.out.push_str(&format!("{}{}:\n", indent, name));
+                    let joined = parts
+                        .iter()
+                        .map(|part| format!("{}{}", inner, part))
+                        .collect::<Vec<_>>()
+                        .join(",\n");
+                    self.out.push_str(&joined);
+                    self.out.push_str(";\n");
+                } else {
+                    self.out.push_str(&line);
+                    self.out.push('\n');
+                }
+            }
+            // `@charset` must be written exactly, double quotes included.
+            Node::Statement(text) if text.starts_with("@charset") => {
+                self.out.push_str(&format!("{}{};\n", indent, text));
+            }
+            Node::Statement(text) => {
+                let text = respace(text, Spacing::AtRulePrelude, self.options);
+                self.out.push_str(&format!("{}{};\n", indent, text));
+            }
+            Node::Block { prelude, children } => {
+                let prelude = self.format_prelude(prelude.trim(), &indent);
+                self.out.push_str(&format!("{}{} {{\n", indent, prelude));
+                self.print_nodes(children, depth + 1);
+                self.out.push_str(&format!("{}}}\n", indent));
+            }
+            Node::BlankLine => {}
+        }
+    }
+
+    /// Selector lists stay on one line when they fit and otherwise get a
+    /// selector per line; at-rule preludes are only respaced.
+    fn format_prelude(&self, prelude: &str, indent: &str) -> String {
+        if prelude.starts_with('@') {
+            return respace(prelude, Spacing::AtRulePrelude, self.options);
+        }
+
+        let selectors: Vec<String> = split_top_level_commas(prelude)
+            .into_iter()
+            .map(|selector| respace(selector, Spacing::Selector, self.options))
+            .collect();
+        let one_line = selectors.join(", ");
+        if indent.len() + one_line.len() + 2 <= self.options.max_line_length {
+            one_line
+        } else {
+            selectors.join(&format!(",\n{}", indent))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        format_css(source, &FormatOptions::default())
+    }
+
+    #[test]
+    fn lays_out_rules_and_declarations() {
+        assert_eq!(
+            format(".a,.b{color:red;margin:0 auto!important}"),
+            ".a, .b {\n  color: red;\n  margin: 0 auto !important;\n}\n"
+        );
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let source = "/* header */\n.a,.b{color:red;/* trailing */\n\n\n  margin:0}\n\
+            .c>.d{--x:{a:b};font-family:'Open Sans'}\n\
+            @import url(a.css);\n\
+            @media (min-width:40em){.e{background:url(a.png)}}\n";
+        let once = format(source);
+        assert_eq!(format(&once), once);
+    }
+
+    #[test]
+    fn keeps_comments_and_blank_lines() {
+        assert_eq!(
+            format("/* header */\n.a{color:red;/* trailing */\n\n\n/* own line */\nmargin:0}"),
+            "/* header */\n.a {\n  color: red; /* trailing */\n\n  /* own line */\n  margin: 0;\n}\n"
+        );
+    }
+
+    #[test]
+    fn leaves_comment_contents_alone() {
+        assert_eq!(
+            format(".a{color:red /* a  ,  b>c */}"),
+            ".a {\n  color: red /* a  ,  b>c */;\n}\n"
+        );
+    }
+
+    #[test]
+    fn copies_escapes_verbatim() {
+        assert_eq!(format(".a\\+b>.c{}"), ".a\\+b > .c {\n}\n");
+        assert_eq!(
+            format(".md\\:flex , .x\\,y{color:red}"),
+            ".md\\:flex, .x\\,y {\n  color: red;\n}\n"
+        );
+        assert_eq!(
+            format(".\\31 0{content:\"\\\"\"}"),
+            ".\\31 0 {\n  content: \"\\\"\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_braces_in_custom_property_values() {
+        assert_eq!(
+            format(".a{--x:{a:b;c:d};color:red}"),
+            ".a {\n  --x: {a:b;c:d};\n  color: red;\n}\n"
+        );
+    }
+
+    #[test]
+    fn formats_a_last_declaration_without_semicolon() {
+        assert_eq!(format(".a{color:red}"), ".a {\n  color: red;\n}\n");
+    }
+}
@@ -0,0 +1,25 @@
+//! PrettyStrict, a CSS linter.
+//!
+//! The `prettystrict` binary is a thin wrapper around this crate. Other
+//! crates can register their own [`lint_rules::LintRule`]s in a
+//! [`lint_rules::RuleRegistry`] and run them through a
+//! [`check_file::Linter`].
+
+// LintError doubles as the application error type, so results carry it as-is.
+#![allow(clippy::result_large_err)]
+
+pub mod check_file;
+pub mod collect_files;
+pub mod config;
+pub mod directives;
+pub mod error;
+pub mod fix;
+pub mod format;
+pub mod lint_rules;
+pub mod parse_css;
+pub mod report;
+pub mod rules;
+pub mod source;
+#[cfg(test)]
+mod testing;
+pub mod watch;
@@ -1,53 +1,259 @@
-use crate::error;
+use crate::config::{RuleSet, Severity};
 use crate::error::PrettystrictError;
-use crate::rules::check_property::{PropertyList, Rule, check_at_rule, check_props};
-use crate::rules::check_value::{ValueList, check_value};
+use crate::fix::Edit;
+use crate::rules::check_property::{NoUnknownAtRule, NoUnknownProperty, Property, PropertyList, Rule};
+use crate::rules::check_value::{NoUnknownValue, ValueList};
 use crate::rules::duplicate_declaration::{
-    Location, check_order, duplicate_declaration, shorthand_detection,
+    NoDuplicateDeclarations, NoShorthandOverride, PropertyOrder,
 };
-use crate::rules::unit_check::unit_check;
+use crate::rules::duplicate_selector::{NoDuplicateDeclarationBlocks, NoDuplicateSelectors};
+use crate::rules::font_face::{
+    FontFaceNoMissingDescriptors, FontFaceRequireDisplay, FontFaceSrcFormat,
+};
+use crate::rules::keyframes::{
+    KeyframeDeclarationNoImportant, KeyframePropertyAnimatable, KeyframeSelectorInRange,
+    KeyframesNoMissingEndpointProperties, NoDuplicateKeyframeOffsets, NoUndefinedKeyframes,
+    NoUnusedKeyframes,
+};
+use crate::rules::media_conflict::NoConflictingMedia;
+use crate::rules::nesting::MaxNestingDepth;
+use crate::rules::unit_check::UnitAllowed;
+use crate::source::Span;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A check run against a stylesheet.
+///
+/// Most rules look at one rule at a time and implement [`LintRule::check`];
+/// rules that compare rules with each other implement
+/// [`LintRule::check_stylesheet`] instead. Implement this and add it to a
+/// [`RuleRegistry`] to lint with rules that are not built in.
+pub trait LintRule {
+    /// The stable id config files and reports refer to the rule by.
+    fn id(&self) -> &'static str;
+
+    /// A one-line summary of what the rule reports.
+    fn description(&self) -> &'static str;
+
+    /// The severity used when no config sets one.
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// The options the rule accepts next to `severity` in its config table.
+    fn options(&self) -> &'static [RuleOption] {
+        &[]
+    }
+
+    /// Checks a single rule; called for every rule in the stylesheet.
+    fn check(&self, _rule: &Rule, _context: &Context) -> Vec<LintError> {
+        Vec::new()
+    }
+
+    /// Checks the stylesheet as a whole; called once per file with every
+    /// rule in source order, each carrying its enclosing at-rules.
+    fn check_stylesheet(&self, _rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        Vec::new()
+    }
+}
+
+/// An option a rule accepts, used to validate config files.
+#[derive(Debug, Clone, Copy)]
+pub struct RuleOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    /// A list of strings, e.g. `["display", "position"]`.
+    StringList,
+    /// A table of string lists, e.g. `{ margin = ["margin-top"] }`.
+    StringListMap,
+    /// A whole number of at least zero, e.g. `3`.
+    Count,
+}
+
+impl OptionKind {
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            OptionKind::StringList => serde_json::from_value::<Vec<String>>(value.clone()).is_ok(),
+            OptionKind::StringListMap => {
+                serde_json::from_value::<HashMap<String, Vec<String>>>(value.clone()).is_ok()
+            }
+            OptionKind::Count => serde_json::from_value::<usize>(value.clone()).is_ok(),
+        }
+    }
+}
+
+/// Everything a rule may consult besides the rule being checked.
+pub struct Context<'a> {
+    pub known_props: &'a PropertyList,
+    pub known_values: &'a ValueList,
+    /// The rule settings in effect for this file.
+    pub settings: &'a RuleSet,
+    pub path: &'a Path,
+    /// The full text of the stylesheet.
+    pub source: &'a str,
+}
 
-#[derive(Debug)]
+/// The rules a run lints with, in the order they are run.
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+impl RuleRegistry {
+    /// A registry without any rules, not even the built-in ones.
+    pub fn empty() -> Self {
+        RuleRegistry { rules: Vec::new() }
+    }
+
+    /// Adds `rule`, replacing any registered rule with the same id.
+    pub fn register(&mut self, rule: impl LintRule + 'static) {
+        let rule: Box<dyn LintRule> = Box::new(rule);
+        match self.rules.iter().position(|r| r.id() == rule.id()) {
+            Some(index) => self.rules[index] = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn LintRule> {
+        self.iter().find(|rule| rule.id() == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn LintRule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+}
+
+impl Default for RuleRegistry {
+    /// The built-in rules.
+    fn default() -> Self {
+        let mut registry = RuleRegistry::empty();
+        registry.register(NoUnknownProperty);
+        registry.register(NoUnknownValue);
+        registry.register(NoDuplicateDeclarations);
+        registry.register(UnitAllowed);
+        registry.register(NoShorthandOverride);
+        registry.register(PropertyOrder);
+        registry.register(NoUnknownAtRule);
+        registry.register(NoDuplicateSelectors);
+        registry.register(NoDuplicateDeclarationBlocks);
+        registry.register(NoUnusedKeyframes);
+        registry.register(NoUndefinedKeyframes);
+        registry.register(NoConflictingMedia);
+        registry.register(MaxNestingDepth);
+        registry.register(FontFaceRequireDisplay);
+        registry.register(FontFaceSrcFormat);
+        registry.register(FontFaceNoMissingDescriptors);
+        registry.register(KeyframeSelectorInRange);
+        registry.register(NoDuplicateKeyframeOffsets);
+        registry.register(KeyframeDeclarationNoImportant);
+        registry.register(KeyframePropertyAnimatable);
+        registry.register(KeyframesNoMissingEndpointProperties);
+        registry
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct LintError {
+    /// Id of the rule that reported this, or the error code for parse and IO failures.
+    pub rule: String,
+    pub severity: Severity,
     pub selector: String,
     pub property: String,
     pub message: String,
+    #[serde(skip)]
     pub kind: PrettystrictError,
+    pub span: Span,
+    /// A suggestion for resolving the diagnostic, when there is an obvious one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    /// Source edits that apply `fix`, when it is safe to do automatically.
+    #[serde(skip)]
+    pub edits: Vec<Edit>,
 }
 
-pub fn lint_rules(
-    rule: &Rule,
-    known_props: &PropertyList,
-    known_values: &ValueList,
-    location: &Location,
-) -> Vec<LintError> {
-    fn convert(rule_errors: Vec<LintError>) -> Vec<LintError> {
-        rule_errors
-            .into_iter()
-            .map(|re| LintError {
-                selector: re.selector,
-                property: re.property,
-                message: format!("{}", re.message),
-                kind: error::PrettystrictError::from(re.kind),
-            })
-            .collect()
+impl LintError {
+    /// A diagnostic about `property` in `rule`; `lint_rules` fills in the
+    /// rule id and severity.
+    pub fn new(rule: &Rule, property: &Property, message: String, kind: PrettystrictError) -> Self {
+        LintError {
+            rule: kind.code().into(),
+            severity: Severity::Error,
+            selector: rule.selector.clone(),
+            property: property.name.clone(),
+            message,
+            kind,
+            span: property.span,
+            fix: None,
+            edits: Vec::new(),
+        }
     }
 
-    let mut errors = Vec::new();
+    /// A diagnostic about `rule` as a whole, pointing at its selector.
+    pub fn for_rule(rule: &Rule, message: String, kind: PrettystrictError) -> Self {
+        LintError {
+            rule: kind.code().into(),
+            severity: Severity::Error,
+            selector: rule.selector.clone(),
+            property: String::new(),
+            message,
+            kind,
+            span: rule.span,
+            fix: None,
+            edits: Vec::new(),
+        }
+    }
+}
 
-    let rule_checks: Vec<Vec<LintError>> = vec![
-        check_props(rule, known_props),
-        check_value(rule, known_values),
-        duplicate_declaration(rule, location),
-        unit_check(rule, known_values),
-        shorthand_detection(rule, known_values),
-        check_order(rule),
-        check_at_rule(rule, known_props),
-    ];
+/// Runs every enabled rule in `registry` against each rule of a stylesheet,
+/// then the stylesheet-wide checks against all of them.
+pub fn lint_rules(rules: &[Rule], context: &Context, registry: &RuleRegistry) -> Vec<LintError> {
+    let enabled: Vec<(&dyn LintRule, Severity)> = registry
+        .iter()
+        .map(|lint_rule| {
+            let severity = context
+                .settings
+                .severity(lint_rule.id())
+                .unwrap_or_else(|| lint_rule.default_severity());
+            (lint_rule, severity)
+        })
+        .filter(|(_, severity)| *severity != Severity::Off)
+        .collect();
 
-    for rule_errors in rule_checks {
-        errors.extend(convert(rule_errors));
+    let mut errors = Vec::new();
+    for rule in rules {
+        for (lint_rule, severity) in &enabled {
+            errors.extend(convert(lint_rule.id(), *severity, lint_rule.check(rule, context)));
+        }
+    }
+    for (lint_rule, severity) in &enabled {
+        errors.extend(convert(
+            lint_rule.id(),
+            *severity,
+            lint_rule.check_stylesheet(rules, context),
+        ));
     }
 
     errors
 }
+
+fn convert(id: &str, severity: Severity, rule_errors: Vec<LintError>) -> Vec<LintError> {
+    rule_errors
+        .into_iter()
+        .map(|re| LintError {
+            rule: id.to_string(),
+            severity,
+            selector: re.selector,
+            property: re.property,
+            message: re.message,
+            kind: re.kind,
+            span: re.span,
+            fix: re.fix,
+            edits: re.edits,
+        })
+        .collect()
+}
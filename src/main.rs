@@ -1,15 +1,10 @@
-mod check_file;
-mod error;
-mod lint_rules;
-mod parse_css;
-mod rules;
+// LintError doubles as the application error type, so results carry it as-is.
+#![allow(clippy::result_large_err)]
 
-use crate::lint_rules::{LintError, lint_rules};
-
-use crate::rules::check_property::load_known_props;
-use crate::rules::check_value::load_known_values;
-use crate::rules::duplicate_declaration::Location;
 use clap::{Parser, Subcommand};
+use prettystrict::check_file::{CheckArgs, check};
+use prettystrict::format::{FormatArgs, format};
+use prettystrict::lint_rules::LintError;
 
 #[derive(Parser, Debug)]
 #[command(name = "prettystrict")]
@@ -20,33 +15,25 @@ struct Cli {
 
 #[derive(Subcommand, Debug, Clone)]
 enum Commands {
-    Check {
-        #[arg(value_name = "FILE")]
-        file: Option<String>,
-
-        #[arg(short, long, default_value_t = false)]
-        watch: bool,
-    },
+    /// Lint stylesheets
+    Check(CheckArgs),
+    /// Pretty-print stylesheets in place
+    Format(FormatArgs),
 }
-fn main() -> Result<(), LintError> {
-    let known_props = load_known_props("./src/CSS/Props.json")?;
-    let known_values = load_known_values("./src/CSS/Values.json")?;
-
-    let rules = parse_css::parse_css_default()?;
-    let location = Location { line: 1, column: 1 }; // dummy values for testing
-
-    let mut all_errors = Vec::new();
-    for rule in &rules {
-        let errors = lint_rules(rule, &known_props, &known_values, &location);
-        all_errors.extend(errors);
+fn main() {
+    let cli = Cli::parse();
+
+    let result: Result<bool, LintError> = match cli.command {
+        Commands::Check(args) => check(args),
+        Commands::Format(args) => format(args),
+    };
+
+    match result {
+        Ok(false) => {}
+        Ok(true) => std::process::exit(1),
+        Err(error) => {
+            eprintln!("prettystrict: {}", error.message);
+            std::process::exit(2);
+        }
     }
-
-    for error in all_errors {
-        println!(
-            "[{}:{}] {}: {}",
-            location.line, location.column, error.property, error.message
-        );
-    }
-
-    Ok(())
 }
@@ -1,152 +1,563 @@
-use std::fmt;
-use lightningcss::error::{ParserError, PrinterErrorKind};
+use lightningcss::declaration::DeclarationBlock;
+use lightningcss::error::PrinterErrorKind;
 use lightningcss::printer::{Printer, PrinterOptions};
 use lightningcss::traits::ToCss;
 use lightningcss::properties::Property as LightningProperty;
-use lightningcss::rules::CssRule;
-use lightningcss::rules::keyframes::{KeyframesName, KeyframesRule};
+use lightningcss::rules::font_face::FontFaceProperty;
+use lightningcss::rules::keyframes::KeyframesName;
+use lightningcss::rules::nesting::NestedDeclarationsRule;
+use lightningcss::rules::style::StyleRule;
+use lightningcss::rules::{CssRule, Location};
 use lightningcss::stylesheet::{ParserOptions, StyleSheet};
 use lightningcss::targets::Targets;
 use crate::error::PrettystrictError;
 use crate::lint_rules::LintError;
-use crate::rules::check_property::{Property, Rule};
+use crate::rules::check_property::{AtRule, AtRuleKind, Property, Rule};
+use crate::source::{Block, DeclarationCursor, Source, Span, skip_string, split_top_level_commas};
 
 
 
 
 // === MAIN ENTRY POINT ===
 pub fn parse_css(css_content: &str) -> Result<Vec<Rule>, LintError> {
+    let source = Source::new(css_content);
     let stylesheet = StyleSheet::parse(css_content, ParserOptions::default()).map_err(|e| LintError {
-        selector: "".to_string(),
-        property: "".to_string(),
         message: format!("Failed to parse CSS: {}", e),
-        kind: PrettystrictError::Custom("parse_css".to_string()),
+        span: e
+            .loc
+            .as_ref()
+            .map(|loc| {
+                let offset = source.offset_of(loc.line, loc.column);
+                source.span(offset..offset)
+            })
+            .unwrap_or_default(),
+        ..LintError::from(PrettystrictError::Custom("parse_css".to_string()))
     })?;
 
-    let mut rules = Vec::new();
-    let mut current_at_rules = Vec::new();
-
+    let mut traversal = Traversal {
+        source: &source,
+        rules: Vec::new(),
+        at_rules: Vec::new(),
+        parents: Vec::new(),
+        depth: 0,
+    };
     for rule in &stylesheet.rules.0 {
-        traverse_rule(rule, &mut rules, &mut current_at_rules);
+        traversal.traverse_rule(rule);
     }
 
-    Ok(rules)
+    Ok(traversal.rules)
 }
 
 // === TRAVERSE RULES ===
-fn traverse_rule(rule: &CssRule, rules: &mut Vec<Rule>, current_at_rules: &mut Vec<String>) {
-    match rule {
-        CssRule::Style(style_rule) => {
-            let selector = to_css_string(&style_rule.selectors);
-            let mut declarations = Vec::new();
-
-            for property in &style_rule.declarations.declarations {
-                if let Some(prop) = extract_property(property) {
-                    declarations.push(prop);
-                }
+/// Collects the lintable rules of a stylesheet, tracking the at-rules that
+/// enclose each one.
+struct Traversal<'a> {
+    source: &'a Source<'a>,
+    rules: Vec<Rule>,
+    at_rules: Vec<AtRule>,
+    /// The indices in `rules` of the style rules enclosing the current one.
+    parents: Vec<usize>,
+    /// How many blocks inside the outermost style rule enclose the current
+    /// rule's block.
+    depth: usize,
+}
+
+impl Traversal<'_> {
+    fn traverse_rule(&mut self, rule: &CssRule) {
+        let source = self.source;
+        match rule {
+            CssRule::Style(style_rule) => self.style_rule(style_rule),
+            CssRule::Nesting(nesting_rule) => self.style_rule(&nesting_rule.style),
+
+            CssRule::Media(media_rule) => {
+                let query = to_css_string_or_error(&media_rule.query);
+                self.group(
+                    AtRuleKind::Media { query },
+                    media_rule.loc,
+                    &media_rule.rules.0,
+                );
             }
 
-            rules.push(Rule {
-                selector,
-                declaration: declarations,
-                at_rule: current_at_rules.clone(),
-            });
-        }
+            CssRule::Supports(supports_rule) => {
+                let condition = to_css_string(&supports_rule.condition);
+                let kind = AtRuleKind::Supports { condition };
+                self.group(kind, supports_rule.loc, &supports_rule.rules.0);
+            }
 
-        CssRule::Media(media_rule) => {
-            let query = to_css_string_or_error(&media_rule.query);
-            let media_query = format!("@media {}", query);
-            current_at_rules.push(media_query);
+            CssRule::Container(container_rule) => {
+                let kind = AtRuleKind::Container {
+                    name: container_rule.name.as_ref().map(to_css_string),
+                    condition: to_css_string(&container_rule.condition),
+                };
+                self.group(kind, container_rule.loc, &container_rule.rules.0);
+            }
 
-            for nested_rule in &media_rule.rules.0 {
-                traverse_rule(nested_rule, rules, current_at_rules);
+            CssRule::LayerBlock(layer_rule) => {
+                let kind = AtRuleKind::Layer {
+                    names: layer_rule.name.iter().map(to_css_string).collect(),
+                };
+                self.group(kind, layer_rule.loc, &layer_rule.rules.0);
             }
 
-            current_at_rules.pop();
-        }
+            CssRule::Scope(scope_rule) => {
+                let kind = AtRuleKind::Scope {
+                    start: scope_rule.scope_start.as_ref().map(to_css_string),
+                    end: scope_rule.scope_end.as_ref().map(to_css_string),
+                };
+                self.group(kind, scope_rule.loc, &scope_rule.rules.0);
+            }
 
-        CssRule::Supports(supports_rule) => {
-            let condition = to_css_string(&supports_rule.condition);
-            current_at_rules.push(format!("@supports {}", condition));
+            CssRule::StartingStyle(starting_style_rule) => {
+                self.group(
+                    AtRuleKind::StartingStyle,
+                    starting_style_rule.loc,
+                    &starting_style_rule.rules.0,
+                );
+            }
 
-            for nested_rule in &supports_rule.rules.0 {
-                traverse_rule(nested_rule, rules, current_at_rules);
+            CssRule::MozDocument(document_rule) => {
+                self.group(
+                    other_at_rule(rule),
+                    document_rule.loc,
+                    &document_rule.rules.0,
+                );
             }
 
-            current_at_rules.pop();
-        }
+            CssRule::Keyframes(keyframes_rule) => {
+                let name = match &keyframes_rule.name {
+                    KeyframesName::Ident(ident) => ident.0.to_string(),
+                    KeyframesName::Custom(name) => name.to_string(),
+                };
+                let (start, at_rule_span) = self.prelude_span(keyframes_rule.loc);
+                let block = scan_rule_block(source, start);
+                self.at_rules.push(AtRule {
+                    kind: AtRuleKind::Keyframes { name },
+                    span: at_rule_span,
+                });
 
-        CssRule::Keyframes(keyframes_rule) => {
-            let mut buffer = String::new();
-            let options = PrinterOptions {
-                minify: false,
-                targets: Targets::default(),
-                analyze_dependencies: None,
-                pseudo_classes: None,
-                source_map: None,
-                project_root: None,
-            };
-            let mut printer = Printer::new(&mut buffer, options);
+                let mut next = 0;
+                for keyframe in &keyframes_rule.keyframes {
+                    let selector = to_css_string(&keyframe.selectors);
+                    // Keyframes carry no location of their own; they appear in
+                    // the same order as the nested blocks of the at-rule, but
+                    // blocks the parser dropped are skipped by their prelude.
+                    let key = keyframe_selector_key(&selector);
+                    let found = block.nested[next..].iter().position(|b| {
+                        keyframe_selector_key(&source.text[b.prelude.clone()]) == key
+                    });
+                    let scanned = found.map(|offset| &block.nested[next + offset]);
+                    if let Some(offset) = found {
+                        next += offset + 1;
+                    }
+                    let span = scanned.map_or(at_rule_span, |b| source.span(b.prelude.clone()));
+                    let mut cursor = scanned.map(|b| b.block.cursor());
+                    let declarations =
+                        extract_declarations(&keyframe.declarations, cursor.as_mut(), source, span);
+                    self.push(selector, declarations, span);
+                }
 
-            // Print the whole keyframes_rule CSS into buffer
-            keyframes_rule.to_css(&mut printer).expect("Failed to convert keyframes_rule to CSS");
+                self.at_rules.pop();
+            }
 
-            current_at_rules.push(buffer);
+            CssRule::FontFace(font_face_rule) => {
+                let (start, span) = self.prelude_span(font_face_rule.loc);
+                let block = scan_rule_block(source, start);
+                let mut cursor = block.cursor();
+
+                let mut declarations: Vec<Property> = font_face_rule
+                    .properties
+                    .iter()
+                    .map(|property| extract_font_face_property(property, &mut cursor, source, span))
+                    .collect();
+                declarations.sort_by_key(|prop| prop.span.start.offset);
+
+                self.push_at_rule(AtRuleKind::FontFace, declarations, span);
+            }
 
-            for keyframe in &keyframes_rule.keyframes {
-                let selector = to_css_string(&keyframe.selectors);
+            CssRule::Page(page_rule) => {
+                let kind = AtRuleKind::Page {
+                    selectors: page_rule.selectors.iter().map(to_css_string).collect(),
+                };
+                let (start, span) = self.prelude_span(page_rule.loc);
+                let block = scan_rule_block(source, start);
+                let declarations = extract_declarations(
+                    &page_rule.declarations,
+                    Some(&mut block.cursor()),
+                    source,
+                    span,
+                );
+
+                self.at_rules.push(AtRule { kind, span });
+                self.push(String::new(), declarations, span);
+                for margin_rule in &page_rule.rules {
+                    let (start, span) = self.prelude_span(margin_rule.loc);
+                    let block = scan_rule_block(source, start);
+                    let declarations = extract_declarations(
+                        &margin_rule.declarations,
+                        Some(&mut block.cursor()),
+                        source,
+                        span,
+                    );
+                    let kind = AtRuleKind::PageMargin {
+                        name: format!("@{}", to_css_string(&margin_rule.margin_box)),
+                    };
+                    self.push_at_rule(kind, declarations, span);
+                }
+                self.at_rules.pop();
+            }
 
-                let mut declarations = Vec::new();
-                for property in &keyframe.declarations.declarations {
-                    if let Some(prop) = extract_property(property) {
-                        declarations.push(prop);
-                    }
+            CssRule::Property(property_rule) => {
+                let (start, span) = self.prelude_span(property_rule.loc);
+                let block = scan_rule_block(source, start);
+                let mut cursor = block.cursor();
+
+                let mut declarations = vec![
+                    extract_descriptor(
+                        "syntax",
+                        to_css_string(&property_rule.syntax),
+                        &mut cursor,
+                        source,
+                        span,
+                    ),
+                    extract_descriptor(
+                        "inherits",
+                        property_rule.inherits.to_string(),
+                        &mut cursor,
+                        source,
+                        span,
+                    ),
+                ];
+                if let Some(initial_value) = &property_rule.initial_value {
+                    let value = to_css_string(initial_value);
+                    declarations.push(extract_descriptor(
+                        "initial-value",
+                        value,
+                        &mut cursor,
+                        source,
+                        span,
+                    ));
                 }
+                declarations.sort_by_key(|prop| prop.span.start.offset);
 
-                rules.push(Rule {
-                    selector,
-                    declaration: declarations,
-                    at_rule: current_at_rules.clone(),
-                });
+                let kind = AtRuleKind::Property {
+                    name: to_css_string(&property_rule.name),
+                };
+                self.push_at_rule(kind, declarations, span);
             }
 
-            current_at_rules.pop();
-        }
+            CssRule::CounterStyle(counter_style_rule) => {
+                let (start, span) = self.prelude_span(counter_style_rule.loc);
+                let block = scan_rule_block(source, start);
+                let declarations = extract_declarations(
+                    &counter_style_rule.declarations,
+                    Some(&mut block.cursor()),
+                    source,
+                    span,
+                );
+                let kind = AtRuleKind::CounterStyle {
+                    name: to_css_string(&counter_style_rule.name),
+                };
+                self.push_at_rule(kind, declarations, span);
+            }
 
+            CssRule::Viewport(viewport_rule) => {
+                let (start, span) = self.prelude_span(viewport_rule.loc);
+                let block = scan_rule_block(source, start);
+                let declarations = extract_declarations(
+                    &viewport_rule.declarations,
+                    Some(&mut block.cursor()),
+                    source,
+                    span,
+                );
+                self.push_at_rule(other_at_rule(rule), declarations, span);
+            }
 
+            // At-rules linted as a whole, without declarations of their own.
+            CssRule::Import(import_rule) => {
+                let kind = AtRuleKind::Import {
+                    url: import_rule.url.to_string(),
+                    layer: import_rule
+                        .layer
+                        .as_ref()
+                        .map(|name| name.as_ref().map(to_css_string).unwrap_or_default()),
+                    supports: import_rule.supports.as_ref().map(to_css_string),
+                    media: (!import_rule.media.media_queries.is_empty())
+                        .then(|| to_css_string(&import_rule.media)),
+                };
+                self.statement(kind, import_rule.loc);
+            }
+            CssRule::Namespace(namespace_rule) => {
+                let kind = AtRuleKind::Namespace {
+                    prefix: namespace_rule
+                        .prefix
+                        .as_ref()
+                        .map(|prefix| prefix.0.to_string()),
+                    url: namespace_rule.url.0.to_string(),
+                };
+                self.statement(kind, namespace_rule.loc);
+            }
+            CssRule::LayerStatement(layer_rule) => {
+                let kind = AtRuleKind::Layer {
+                    names: layer_rule.names.iter().map(to_css_string).collect(),
+                };
+                self.statement(kind, layer_rule.loc);
+            }
+            CssRule::CustomMedia(rule_data) => self.statement(other_at_rule(rule), rule_data.loc),
+            CssRule::FontPaletteValues(rule_data) => {
+                self.statement(other_at_rule(rule), rule_data.loc)
+            }
+            CssRule::FontFeatureValues(rule_data) => {
+                self.statement(other_at_rule(rule), rule_data.loc)
+            }
+            CssRule::ViewTransition(rule_data) => {
+                self.statement(other_at_rule(rule), rule_data.loc)
+            }
+            CssRule::Unknown(rule_data) => self.statement(other_at_rule(rule), rule_data.loc),
 
+            // Nested declarations are added by the rule whose block holds them.
+            CssRule::NestedDeclarations(_) | CssRule::Ignored | CssRule::Custom(_) => {}
+        }
+    }
 
-            CssRule::FontFace(font_face_rule) => {
-            let mut declarations = Vec::new();
-            for property in &font_face_rule.properties {
-                if let Some(prop) = extract_font_face_property(property) {
-                    declarations.push(prop);
+    /// Adds a style rule, followed by the rules nested in it.
+    fn style_rule(&mut self, style_rule: &StyleRule) {
+        let source = self.source;
+        let selector = to_css_string(&style_rule.selectors);
+        let selector = match self.parent_selector() {
+            Some(parent) => resolve_selector(&parent, &selector),
+            None => selector,
+        };
+        let start = source.offset_of(style_rule.loc.line, style_rule.loc.column);
+        let block = scan_rule_block(source, start);
+        let span = source.span(source.trim(start..block.open));
+        let mut cursor = block.cursor();
+        let declarations =
+            extract_declarations(&style_rule.declarations, Some(&mut cursor), source, span);
+
+        let index = self.rules.len();
+        self.push(selector, declarations, span);
+        self.parents.push(index);
+        self.depth += 1;
+        for nested in &style_rule.rules.0 {
+            match nested {
+                // Declarations after a nested rule still belong to this rule.
+                CssRule::NestedDeclarations(nested) => {
+                    let declarations =
+                        extract_declarations(&nested.declarations, Some(&mut cursor), source, span);
+                    self.rules[index].declaration.extend(declarations);
                 }
+                _ => self.traverse_rule(nested),
             }
+        }
+        self.depth -= 1;
+        self.parents.pop();
+    }
 
-            rules.push(Rule {
-                selector: "".to_string(),
-                declaration: declarations,
-                at_rule: vec!["@font-face".to_string()],
-            });
+    /// Adds the declarations written directly in a conditional rule nested in
+    /// a style rule; they apply to that style rule's selector.
+    fn nested_declarations(
+        &mut self,
+        nested: &NestedDeclarationsRule,
+        cursor: &mut DeclarationCursor,
+    ) {
+        let Some(selector) = self.parent_selector() else {
+            return;
+        };
+        let source = self.source;
+        let start = source.offset_of(nested.loc.line, nested.loc.column);
+        let span = source.span(start..start);
+        let declarations = extract_declarations(&nested.declarations, Some(cursor), source, span);
+        let span = declarations.first().map_or(span, |d| d.span);
+        self.push(selector, declarations, span);
+    }
+
+    fn parent_selector(&self) -> Option<String> {
+        self.parents
+            .last()
+            .map(|&index| self.rules[index].selector.clone())
+    }
+
+    /// Traverses the rules of a conditional or grouping at-rule.
+    fn group(&mut self, kind: AtRuleKind, loc: Location, list: &[CssRule]) {
+        let (start, span) = self.prelude_span(loc);
+        let block = scan_rule_block(self.source, start);
+        let mut cursor = block.cursor();
+        // Inside a style rule, a conditional rule adds a level of nesting.
+        let level = usize::from(!self.parents.is_empty());
+
+        self.at_rules.push(AtRule { kind, span });
+        for rule in list {
+            if let CssRule::NestedDeclarations(nested) = rule {
+                self.nested_declarations(nested, &mut cursor);
+            } else {
+                self.depth += level;
+                self.traverse_rule(rule);
+                self.depth -= level;
+            }
+        }
+        self.at_rules.pop();
+    }
+
+    /// Adds an at-rule without a block of declarations, such as `@import`.
+    fn statement(&mut self, kind: AtRuleKind, loc: Location) {
+        let (_, span) = self.prelude_span(loc);
+        self.push_at_rule(kind, Vec::new(), span);
+    }
+
+    /// The start of the rule at `loc` and the span of its prelude.
+    fn prelude_span(&self, loc: Location) -> (usize, Span) {
+        let start = self.source.offset_of(loc.line, loc.column);
+        let end = self.source.prelude_end(start);
+        (start, self.source.span(self.source.trim(start..end)))
+    }
+
+    fn push(&mut self, selector: String, declarations: Vec<Property>, span: Span) {
+        self.rules.push(Rule {
+            selector,
+            declaration: declarations,
+            at_rule: self.at_rules.clone(),
+            nesting_depth: self.depth,
+            span,
+        });
+    }
+
+    /// Adds an at-rule that is linted on its own, such as `@font-face`.
+    fn push_at_rule(&mut self, kind: AtRuleKind, declarations: Vec<Property>, span: Span) {
+        self.at_rules.push(AtRule { kind, span });
+        self.push(String::new(), declarations, span);
+        self.at_rules.pop();
+    }
+}
+
+/// The prelude of an at-rule as printed, such as `@import "a.css" layer`.
+fn prelude(rule: &CssRule) -> String {
+    let printed = to_css_string(rule);
+    printed
+        .split(['{', ';'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Combines a nested selector with its parent's: `&` stands for the parent,
+/// and a selector without one is a descendant of it. When the parent is a
+/// list and `&` appears more than once, every combination of its entries is
+/// produced, as `:is()` would match them.
+fn resolve_selector(parent: &str, selector: &str) -> String {
+    let parents = split_top_level_commas(parent);
+    let mut resolved = Vec::new();
+    for parent in &parents {
+        for nested in split_top_level_commas(selector) {
+            let ampersands = nesting_selectors(nested);
+            let Some((&first, rest)) = ampersands.split_first() else {
+                resolved.push(format!("{} {}", parent, nested));
+                continue;
+            };
+
+            let mut combinations = vec![format!("{}{}", &nested[..first], parent)];
+            let mut previous = first;
+            for &next in rest {
+                let between = &nested[previous + 1..next];
+                combinations = combinations
+                    .iter()
+                    .flat_map(|start| {
+                        parents
+                            .iter()
+                            .map(move |p| format!("{}{}{}", start, between, p))
+                    })
+                    .collect();
+                previous = next;
+            }
+            let tail = &nested[previous + 1..];
+            resolved.extend(combinations.into_iter().map(|start| start + tail));
+        }
+    }
+    resolved.join(", ")
+}
+
+/// The offsets of the `&`s in `selector`, skipping strings, escapes and
+/// attribute selectors, where `&` is only a character.
+fn nesting_selectors(selector: &str) -> Vec<usize> {
+    let bytes = selector.as_bytes();
+    let mut offsets = Vec::new();
+    let mut in_attribute = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' | b'\'' => i = skip_string(selector, i),
+            b'[' => {
+                in_attribute = true;
+                i += 1;
+            }
+            b']' => {
+                in_attribute = false;
+                i += 1;
+            }
+            b'&' if !in_attribute => {
+                offsets.push(i);
+                i += 1;
+            }
+            _ => i += 1,
         }
+    }
+    offsets
+}
+
+/// A keyframe selector reduced to what printing it does not change: case,
+/// spacing and the way each percentage is written.
+fn keyframe_selector_key(selector: &str) -> Vec<String> {
+    selector
+        .split(',')
+        .map(|part| {
+            let part = part.split_whitespace().collect::<Vec<_>>().join(" ");
+            let part = part.to_ascii_lowercase();
+            match part.strip_suffix('%').and_then(|n| n.parse::<f64>().ok()) {
+                Some(percentage) => format!("{}%", percentage),
+                None => part,
+            }
+        })
+        .collect()
+}
+
+/// Describes an at-rule that has no kind of its own by its printed prelude.
+fn other_at_rule(rule: &CssRule) -> AtRuleKind {
+    let printed = prelude(rule);
+    let (name, prelude) = printed.split_once(' ').unwrap_or((&printed, ""));
+    AtRuleKind::Other {
+        name: name.to_string(),
+        prelude: prelude.trim().to_string(),
+    }
+}
 
-        _ => { /* Skip other rules */ }
+/// Locates and scans the `{ ... }` block of the rule that starts at `start`.
+fn scan_rule_block(source: &Source, start: usize) -> Block {
+    match source.find_block_start(start) {
+        Some(open) => source.scan_block(open),
+        None => Block {
+            open: start,
+            close: start,
+            ..Block::default()
+        },
     }
 }
 
 // === FORMATTERS ===
-fn to_css_string<T: ToCss>(value: &T) -> String {
-    let mut buffer = String::new();
-    let options = PrinterOptions {
+fn printer_options<'a>() -> PrinterOptions<'a> {
+    PrinterOptions {
         minify: false,
         targets: Targets::default(),
         analyze_dependencies: None,
         pseudo_classes: None,
         source_map: None,
         project_root: None,
-    };
-    let mut printer = Printer::new(&mut buffer, options);
+    }
+}
+
+fn to_css_string<T: ToCss>(value: &T) -> String {
+    let mut buffer = String::new();
+    let mut printer = Printer::new(&mut buffer, printer_options());
     if let Err(e) = value.to_css(&mut printer) {
         eprintln!("⚠️ CSS printing failed: {:?}", e);
     }
@@ -155,15 +566,7 @@ fn to_css_string<T: ToCss>(value: &T) -> String {
 
 fn to_css_string_or_error<T: ToCss>(value: &T) -> String {
     let mut buffer = String::new();
-    let options = PrinterOptions {
-        minify: false,
-        targets: Targets::default(),
-        analyze_dependencies: None,
-        pseudo_classes: None,
-        source_map: None,
-        project_root: None,
-    };
-    let mut printer = Printer::new(&mut buffer, options);
+    let mut printer = Printer::new(&mut buffer, printer_options());
     value.to_css(&mut printer).map_err(|e| match e.kind {
         PrinterErrorKind::AmbiguousUrlInCustomProperty { .. } |
         PrinterErrorKind::FmtError |
@@ -177,52 +580,116 @@ fn to_css_string_or_error<T: ToCss>(value: &T) -> String {
 }
 
 // === PROPERTY EXTRACTORS ===
-fn extract_property(property: &LightningProperty) -> Option<Property> {
+/// Converts the normal and `!important` declarations of a block back into
+/// source order, locating each one through `cursor` and falling back to the
+/// rule's `span`.
+fn extract_declarations(
+    block: &DeclarationBlock,
+    mut cursor: Option<&mut DeclarationCursor>,
+    source: &Source,
+    span: Span,
+) -> Vec<Property> {
+    let normal = block.declarations.iter().map(|property| (property, false));
+    let important = block
+        .important_declarations
+        .iter()
+        .map(|property| (property, true));
+
+    let mut declarations: Vec<Property> = normal
+        .chain(important)
+        .filter_map(|(property, important)| {
+            // Locate the declaration even when it is skipped, so later ones
+            // with the same name still line up with their source text.
+            let located = cursor
+                .as_mut()
+                .and_then(|c| c.take(&property_name(property), important));
+            let mut prop = extract_property(property, important, span)?;
+            if let Some(located) = located {
+                prop.span = source.span(located.range.clone());
+                prop.raw_value = source.text[located.value.clone()].to_string();
+            }
+            Some(prop)
+        })
+        .collect();
+    declarations.sort_by_key(|prop| prop.span.start.offset);
+    declarations
+}
+
+/// The name of `property` as written, including any vendor prefix.
+fn property_name(property: &LightningProperty) -> String {
+    let id = property.property_id();
+    format!("{}{}", to_css_string(&id.prefix()), id.name())
+}
+
+/// Converts a parsed declaration, or returns `None` when its value cannot be
+/// serialized. Such a declaration is left out rather than linted with a
+/// made-up value.
+fn extract_property(property: &LightningProperty, important: bool, span: Span) -> Option<Property> {
     use LightningProperty::*;
 
-    let (name, value_opt): (&str, Option<String>) = match property {
-        BackgroundColor(color) => ("background-color", Some(format_css_color(color))),
-        Color(color) => ("color", Some(format_css_color(color))),
-        Width(v) => ("width", Some(to_css_string(v))),
-        Height(v) => ("height", Some(to_css_string(v))),
-        Margin(v) => ("margin", Some(to_css_string(v))),
-        Padding(v) => ("padding", Some(to_css_string(v))),
-        Display(v) => ("display", Some(to_css_string(v))),
-        Position(v) => ("position", Some(to_css_string(v))),
-        FontSize(v) => ("font-size", Some(to_css_string(v))),
-        FontWeight(v) => ("font-weight", Some(to_css_string(v))),
-        FontFamily(v) => ("font-family", Some(to_css_string(v))),
-        TextAlign(v) => ("text-align", Some(to_css_string(v))),
-        Border(v) => ("border", Some(to_css_string(v))),
-        BorderRadius(v, _) => ("border-radius", Some(to_css_string(v))),
-        FlexDirection(v, _) => ("flex-direction", Some(to_css_string(v))),
-        JustifyContent(v, _) => ("justify-content", Some(to_css_string(v))),
-        AlignItems(v, _) => ("align-items", Some(to_css_string(v))),
-        BoxShadow(v, _) => ("box-shadow", Some(to_css_string(v))),
-        Transform(v, _) => ("transform", Some(to_css_string(v))),
-        Opacity(v) => ("opacity", Some(to_css_string(v))),
-        ZIndex(v) => ("z-index", Some(to_css_string(v))),
-        Overflow(v) => ("overflow", Some(to_css_string(v))),
-        Cursor(v) => ("cursor", Some(to_css_string(v))),
-        Visibility(v) => ("visibility", Some(to_css_string(v))),
-        BoxSizing(v, _) => ("box-sizing", Some(to_css_string(v))),
-        TextDecoration(v, _) => ("text-decoration", Some(to_css_string(v))),
-        _ => return None,
+    let id = property.property_id();
+    let prefix = to_css_string(&id.prefix());
+    let value = match property {
+        BackgroundColor(color) | Color(color) => format_css_color(color),
+        _ => property.value_to_css_string(printer_options()).ok()?,
     };
 
-    value_opt.map(|value| Property {
-        name: name.to_string(),
+    Some(Property {
+        name: property_name(property),
+        raw_value: value.clone(),
         value,
+        prefix: (!prefix.is_empty()).then_some(prefix),
+        important,
+        span,
     })
 }
 
+/// Builds a descriptor of an at-rule that has no generic declaration block.
+fn extract_descriptor(
+    name: &str,
+    value: String,
+    cursor: &mut DeclarationCursor,
+    source: &Source,
+    span: Span,
+) -> Property {
+    let located = cursor.take(name, false);
+    Property {
+        name: name.to_string(),
+        raw_value: located.map_or_else(
+            || value.clone(),
+            |d| source.text[d.value.clone()].to_string(),
+        ),
+        value,
+        prefix: None,
+        important: false,
+        span: located.map_or(span, |d| source.span(d.range.clone())),
+    }
+}
+
 fn extract_font_face_property(
-    property: &lightningcss::rules::font_face::FontFaceProperty,
-) -> Option<Property> {
-    Some(Property {
-        name: "font-face-property".to_string(),
-        value: to_css_string(property),
-    })
+    property: &FontFaceProperty,
+    cursor: &mut DeclarationCursor,
+    source: &Source,
+    span: Span,
+) -> Property {
+    let name = match property {
+        FontFaceProperty::Source(_) => "src",
+        FontFaceProperty::FontFamily(_) => "font-family",
+        FontFaceProperty::FontStyle(_) => "font-style",
+        FontFaceProperty::FontWeight(_) => "font-weight",
+        FontFaceProperty::FontStretch(_) => "font-stretch",
+        FontFaceProperty::UnicodeRange(_) => "unicode-range",
+        // Descriptors the parser has no type for, such as `font-display`.
+        FontFaceProperty::Custom(custom) => custom.name.as_ref(),
+    };
+    // Descriptors print as `name: value`.
+    let printed = to_css_string(property);
+    let value = printed
+        .split_once(':')
+        .map_or(printed.as_str(), |(_, value)| value)
+        .trim()
+        .to_string();
+    extract_descriptor(name, value, cursor, source, span)
 }
 
 // === COLOR FORMATTER ===
@@ -247,17 +714,14 @@ fn format_css_color(color: &lightningcss::values::color::CssColor) -> String {
 }
 
 // === FILE IO + FALLBACK ===
-pub fn parse_css_file(file_path: &str) -> Result<Vec<Rule>, LintError> {
-    let css_content = std::fs::read_to_string(file_path).map_err(|e| LintError {
-        selector: "".to_string(),
-        property: "".to_string(),
+pub fn read_css_file(file_path: &str) -> Result<String, LintError> {
+    std::fs::read_to_string(file_path).map_err(|e| LintError {
         message: format!("Failed to read CSS file: {}", e),
-        kind: PrettystrictError::IoError(e),
-    })?;
-
-    parse_css(&css_content)
+        ..LintError::from(PrettystrictError::IoError(e))
+    })
 }
 
+#[allow(dead_code)]
 pub fn parse_css_with_recovery(css_content: &str) -> Result<Vec<Rule>, LintError> {
     match parse_css(css_content) {
         Ok(rules) => Ok(rules),
@@ -292,6 +756,8 @@ fn parse_css_fallback(css_content: &str) -> Result<Vec<Rule>, LintError> {
                 selector,
                 declaration: Vec::new(),
                 at_rule: extract_at_rules_simple(css_content),
+                nesting_depth: 0,
+                span: Span::default(),
             });
             in_rule = true;
         } else if trimmed.contains('}') && brace_count == 0 {
@@ -299,12 +765,12 @@ fn parse_css_fallback(css_content: &str) -> Result<Vec<Rule>, LintError> {
                 rules.push(rule);
             }
             in_rule = false;
-        } else if in_rule && trimmed.contains(':') {
-            if let Some(ref mut rule) = current_rule {
-                if let Some(prop) = parse_declaration_simple(trimmed) {
-                    rule.declaration.push(prop);
-                }
-            }
+        } else if in_rule
+            && trimmed.contains(':')
+            && let Some(ref mut rule) = current_rule
+            && let Some(prop) = parse_declaration_simple(trimmed)
+        {
+            rule.declaration.push(prop);
         }
     }
 
@@ -314,21 +780,33 @@ fn parse_css_fallback(css_content: &str) -> Result<Vec<Rule>, LintError> {
 fn parse_declaration_simple(decl: &str) -> Option<Property> {
     let parts: Vec<&str> = decl.splitn(2, ':').collect();
     if parts.len() == 2 {
+        let value = parts[1].trim().trim_end_matches(';').to_string();
         Some(Property {
             name: parts[0].trim().to_string(),
-            value: parts[1].trim().trim_end_matches(';').to_string(),
+            raw_value: value.clone(),
+            value,
+            prefix: None,
+            important: false,
+            span: Span::default(),
         })
     } else {
         None
     }
 }
 
-fn extract_at_rules_simple(css: &str) -> Vec<String> {
+fn extract_at_rules_simple(css: &str) -> Vec<AtRule> {
     css.lines()
         .filter_map(|line| {
-            let trimmed = line.trim();
+            let trimmed = line.trim().trim_end_matches('{').trim_end();
             if trimmed.starts_with('@') {
-                Some(trimmed.to_string())
+                let (name, prelude) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+                Some(AtRule {
+                    kind: AtRuleKind::Other {
+                        name: name.to_string(),
+                        prelude: prelude.trim().to_string(),
+                    },
+                    span: Span::default(),
+                })
             } else {
                 None
             }
@@ -336,19 +814,147 @@ fn extract_at_rules_simple(css: &str) -> Vec<String> {
         .collect()
 }
 
-pub fn parse_css_default() -> Result<Vec<Rule>, LintError> {
-    let css_content = r#"
-        .foo {
-            color: red;
-            font-size: 16px;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        @media (max-width: 600px) {
-            .bar {
-                background: blue;
-            }
-        }
-    "#;
+    fn selectors(source: &str) -> Vec<String> {
+        parse_css(source)
+            .expect("test CSS parses")
+            .into_iter()
+            .map(|rule| rule.selector)
+            .collect()
+    }
+
+    /// The selector and start line of every rule in `source`.
+    fn rule_lines(source: &str) -> Vec<(String, usize)> {
+        parse_css(source)
+            .expect("test CSS parses")
+            .into_iter()
+            .map(|rule| (rule.selector, rule.span.start.line))
+            .collect()
+    }
+
+    #[test]
+    fn locates_keyframes_by_their_selector() {
+        let source = "@keyframes a {\n  FROM { opacity: 0 }\n  50.0% { opacity: 1 }\n  to { opacity: 0 }\n}\n";
+        assert_eq!(
+            rule_lines(source),
+            [
+                ("from".to_string(), 2),
+                ("50%".to_string(), 3),
+                ("to".to_string(), 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_keyframes_the_parser_dropped() {
+        let source = "@keyframes a {\n  from { opacity: 0 }\n  nope { opacity: 1 }\n  to { opacity: 1 }\n}\n";
+        let rules = parse_css(source).expect("test CSS parses");
+        assert_eq!(rules.len(), 2);
+        let to = &rules[1];
+        assert_eq!(to.span.start.line, 4);
+        assert_eq!(to.declaration[0].span.start.line, 4);
+    }
+
+    #[test]
+    fn resolves_nested_selectors() {
+        assert_eq!(
+            selectors(".c { color: red; &:hover { color: blue; } .d { color: green; } }"),
+            [".c", ".c:hover", ".c .d"]
+        );
+    }
+
+    #[test]
+    fn leaves_ampersands_in_attribute_values_alone() {
+        assert_eq!(
+            resolve_selector(".c", "[data-x=\"&\"]"),
+            ".c [data-x=\"&\"]"
+        );
+        assert_eq!(resolve_selector(".c", "&[data-x='&']"), ".c[data-x='&']");
+        assert_eq!(resolve_selector(".c", "[data-x=a&b]"), ".c [data-x=a&b]");
+        assert_eq!(resolve_selector(".c", ".a\\&b"), ".c .a\\&b");
+        assert_eq!(
+            selectors(".c { [data-x=\"&\"] { color: red; } }"),
+            [".c", ".c [data-x=\"&\"]"]
+        );
+    }
+
+    #[test]
+    fn combines_every_parent_entry_for_repeated_ampersands() {
+        assert_eq!(
+            resolve_selector(".c .e, .c .f", "& + &"),
+            ".c .e + .c .e, .c .e + .c .f, .c .f + .c .e, .c .f + .c .f"
+        );
+        assert_eq!(
+            selectors(".c { .e, .f { & + & { color: red; } } }"),
+            [
+                ".c",
+                ".c .e, .c .f",
+                ".c .e + .c .e, .c .e + .c .f, .c .f + .c .e, .c .f + .c .f",
+            ]
+        );
+    }
 
-    parse_css(css_content)
+    #[test]
+    fn substitutes_a_single_ampersand_per_parent_entry() {
+        assert_eq!(resolve_selector(".a, .b", "&:hover"), ".a:hover, .b:hover");
+        assert_eq!(resolve_selector(".a, .b", ".x &"), ".x .a, .x .b");
+    }
+
+    /// The start line of every rule in `source` with the names of the
+    /// at-rules enclosing it.
+    fn rule_contexts(source: &str) -> Vec<(usize, String)> {
+        parse_css(source)
+            .expect("test CSS parses")
+            .into_iter()
+            .map(|rule| {
+                let names: Vec<&str> = rule.at_rule.iter().map(|a| a.kind.name()).collect();
+                (rule.span.start.line, names.join(" "))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn descends_into_every_grouping_at_rule() {
+        let source = "@layer base {\n  .a { color: red }\n  @container card (width > 30em) {\n    \
+            .b { color: red }\n  }\n}\n@scope (.card) {\n  .c { color: red }\n}\n\
+            @starting-style {\n  .d { opacity: 0 }\n}\n";
+        assert_eq!(
+            rule_contexts(source),
+            [
+                (2, "@layer".to_string()),
+                (4, "@layer @container".to_string()),
+                (8, "@scope".to_string()),
+                (11, "@starting-style".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_page_and_margin_rules() {
+        let source = "@page :first {\n  margin: 1in;\n  @top-left {\n    content: 'x';\n  }\n}\n";
+        let rules = parse_css(source).expect("test CSS parses");
+        assert_eq!(
+            rule_contexts(source),
+            [(1, "@page".to_string()), (3, "@page @top-left".to_string())]
+        );
+        assert_eq!(rules[0].declaration[0].name, "margin");
+        assert_eq!(rules[1].declaration[0].span.start.line, 4);
+    }
+
+    #[test]
+    fn lints_statement_at_rules_on_their_own() {
+        let source = "@import url(a.css) layer(base);\n@namespace svg url(http://www.w3.org/2000/svg);\n\
+            .a { color: red }\n";
+        assert_eq!(
+            rule_contexts(source),
+            [
+                (1, "@import".to_string()),
+                (2, "@namespace".to_string()),
+                (3, "".to_string())
+            ]
+        );
+    }
 }
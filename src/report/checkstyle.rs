@@ -0,0 +1,42 @@
+use crate::check_file::FileReport;
+use crate::config::Severity;
+use crate::report::xml_escape;
+use std::fmt::Write;
+
+/// Renders a Checkstyle XML report with one `<error>` per diagnostic.
+pub fn render(reports: &[&FileReport]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<checkstyle version=\"4.3\">\n");
+
+    for report in reports {
+        let _ = writeln!(
+            out,
+            "  <file name=\"{}\">",
+            xml_escape(&report.path.to_string_lossy())
+        );
+        for error in &report.errors {
+            let _ = writeln!(
+                out,
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"prettystrict.{}\"/>",
+                error.span.start.line,
+                error.span.start.column,
+                severity(error.severity),
+                xml_escape(&error.message),
+                xml_escape(&error.rule)
+            );
+        }
+        out.push_str("  </file>\n");
+    }
+
+    out.push_str("</checkstyle>\n");
+    out
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+        Severity::Off => "ignore",
+    }
+}
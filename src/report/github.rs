@@ -0,0 +1,52 @@
+use crate::check_file::FileReport;
+use crate::config::Severity;
+use std::fmt::Write;
+
+/// Renders GitHub Actions workflow commands, which the runner turns into
+/// inline annotations on the pull request.
+pub fn render(reports: &[&FileReport]) -> String {
+    let mut out = String::new();
+
+    for report in reports {
+        let file = escape_property(&report.path.to_string_lossy());
+        for error in &report.errors {
+            let command = match error.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "notice",
+                Severity::Off => continue,
+            };
+
+            let _ = write!(out, "::{} file={}", command, file);
+            // A zero line means the error has no position in the file.
+            if error.span.start.line > 0 {
+                let _ = write!(
+                    out,
+                    ",line={},col={},endLine={},endColumn={}",
+                    error.span.start.line,
+                    error.span.start.column,
+                    error.span.end.line,
+                    error.span.end.column
+                );
+            }
+            let _ = writeln!(
+                out,
+                ",title={}::{}",
+                escape_property(&error.rule),
+                escape_data(&error.message)
+            );
+        }
+    }
+
+    out
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
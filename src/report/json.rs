@@ -0,0 +1,28 @@
+use crate::check_file::FileReport;
+use crate::error::PrettystrictError;
+use crate::lint_rules::LintError;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    file: &'a Path,
+    #[serde(flatten)]
+    error: &'a LintError,
+}
+
+pub fn render(reports: &[&FileReport]) -> Result<String, LintError> {
+    let diagnostics: Vec<JsonDiagnostic> = reports
+        .iter()
+        .flat_map(|report| {
+            report.errors.iter().map(|error| JsonDiagnostic {
+                file: &report.path,
+                error,
+            })
+        })
+        .collect();
+
+    let mut json = serde_json::to_string_pretty(&diagnostics).map_err(PrettystrictError::from)?;
+    json.push('\n');
+    Ok(json)
+}
@@ -0,0 +1,82 @@
+use crate::check_file::FileReport;
+use crate::lint_rules::{LintError, RuleRegistry};
+use crate::report::xml_escape;
+use std::fmt::Write;
+
+/// Renders a JUnit XML report: a test suite per file, with one test case per
+/// rule that fails when the rule reported anything for that file.
+pub fn render(reports: &[&FileReport], registry: &RuleRegistry) -> String {
+    let mut suites = String::new();
+    let mut total_tests = 0;
+    let mut total_failures = 0;
+
+    for report in reports {
+        let file = xml_escape(&report.path.to_string_lossy());
+
+        // Every rule gets a case; codes from parse and IO failures are added
+        // after them in the order they occur.
+        let mut rule_ids: Vec<&str> = registry.iter().map(|rule| rule.id()).collect();
+        for error in &report.errors {
+            if !rule_ids.contains(&error.rule.as_str()) {
+                rule_ids.push(&error.rule);
+            }
+        }
+
+        let mut cases = String::new();
+        let mut failures = 0;
+        for id in &rule_ids {
+            let errors: Vec<&LintError> = report
+                .errors
+                .iter()
+                .filter(|error| error.rule == *id)
+                .collect();
+
+            if errors.is_empty() {
+                let _ = writeln!(
+                    cases,
+                    "    <testcase classname=\"{}\" name=\"{}\"/>",
+                    file,
+                    xml_escape(id)
+                );
+                continue;
+            }
+
+            failures += 1;
+            let details: Vec<String> = errors
+                .iter()
+                .map(|error| {
+                    format!(
+                        "{}:{}: {}",
+                        error.span.start.line, error.span.start.column, error.message
+                    )
+                })
+                .collect();
+            let _ = writeln!(
+                cases,
+                "    <testcase classname=\"{}\" name=\"{}\">\n      <failure message=\"{} problem{}\" type=\"{}\">{}</failure>\n    </testcase>",
+                file,
+                xml_escape(id),
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" },
+                xml_escape(id),
+                xml_escape(&details.join("\n"))
+            );
+        }
+
+        let _ = writeln!(
+            suites,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n{}  </testsuite>",
+            file,
+            rule_ids.len(),
+            failures,
+            cases
+        );
+        total_tests += rule_ids.len();
+        total_failures += failures;
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"PrettyStrict\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n{}</testsuites>\n",
+        total_tests, total_failures, suites
+    )
+}
@@ -0,0 +1,99 @@
+pub mod checkstyle;
+pub mod github;
+pub mod json;
+pub mod junit;
+pub mod sarif;
+pub mod text;
+
+use crate::check_file::FileReport;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{LintError, RuleRegistry};
+use clap::ValueEnum;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable report grouped by file
+    #[default]
+    Text,
+    /// JSON array of diagnostics
+    Json,
+    /// SARIF 2.1.0 log for code-scanning tools
+    Sarif,
+    /// Checkstyle XML
+    Checkstyle,
+    /// JUnit XML with a test case per file and rule
+    Junit,
+    /// GitHub Actions workflow commands for inline annotations
+    Github,
+}
+
+/// Where and how a run's reports are written.
+pub struct Output {
+    pub format: OutputFormat,
+    /// When set, the formatted report goes here and stdout gets the text report.
+    pub file: Option<PathBuf>,
+}
+
+impl Output {
+    /// Whether stdout receives the human-readable text report.
+    pub fn prints_text(&self) -> bool {
+        self.file.is_some() || self.format == OutputFormat::Text
+    }
+
+    /// `registry` supplies the rule descriptions some formats include.
+    pub fn emit(&self, reports: &[&FileReport], registry: &RuleRegistry) -> Result<(), LintError> {
+        match &self.file {
+            Some(path) => {
+                let rendered = render(self.format, reports, registry)?;
+                fs::write(path, rendered).map_err(PrettystrictError::from)?;
+                print!("{}", text::render(reports, use_color()));
+            }
+            None if self.format == OutputFormat::Text => {
+                print!("{}", text::render(reports, use_color()));
+            }
+            None => print!("{}", render(self.format, reports, registry)?),
+        }
+        Ok(())
+    }
+}
+
+/// Colour is used only on a terminal, and never when `NO_COLOR` is set.
+fn use_color() -> bool {
+    std::io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Renders `reports` in the requested format.
+pub fn render(
+    format: OutputFormat,
+    reports: &[&FileReport],
+    registry: &RuleRegistry,
+) -> Result<String, LintError> {
+    match format {
+        OutputFormat::Text => Ok(text::render(reports, false)),
+        OutputFormat::Json => json::render(reports),
+        OutputFormat::Sarif => sarif::render(reports, registry),
+        OutputFormat::Checkstyle => Ok(checkstyle::render(reports)),
+        OutputFormat::Junit => Ok(junit::render(reports, registry)),
+        OutputFormat::Github => Ok(github::render(reports)),
+    }
+}
+
+/// Escapes text for use in XML attributes and element content.
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
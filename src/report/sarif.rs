@@ -0,0 +1,141 @@
+use crate::check_file::FileReport;
+use crate::config::Severity;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{LintError, RuleRegistry};
+use serde_json::{Value, json};
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders a SARIF 2.1.0 log with one run covering every linted file.
+pub fn render(reports: &[&FileReport], registry: &RuleRegistry) -> Result<String, LintError> {
+    let mut rule_ids: Vec<String> = registry.iter().map(|rule| rule.id().to_string()).collect();
+    let mut descriptors: Vec<Value> = registry
+        .iter()
+        .map(|rule| rule_descriptor(rule.id(), rule.description(), rule.default_severity()))
+        .collect();
+
+    let mut results = Vec::new();
+    for report in reports {
+        for error in &report.errors {
+            // Parse and IO failures use the error code as their rule id and
+            // need a descriptor of their own.
+            let index = match rule_ids.iter().position(|id| *id == error.rule) {
+                Some(index) => index,
+                None => {
+                    rule_ids.push(error.rule.clone());
+                    descriptors.push(rule_descriptor(
+                        &error.rule,
+                        "Stylesheet could not be checked",
+                        Severity::Error,
+                    ));
+                    rule_ids.len() - 1
+                }
+            };
+            results.push(result(&report.path, error, index));
+        }
+    }
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "PrettyStrict",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": descriptors,
+                }
+            },
+            // Columns count characters, not the UTF-16 units SARIF assumes.
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    });
+
+    let mut sarif = serde_json::to_string_pretty(&log).map_err(PrettystrictError::from)?;
+    sarif.push('\n');
+    Ok(sarif)
+}
+
+fn rule_descriptor(id: &str, description: &str, severity: Severity) -> Value {
+    json!({
+        "id": id,
+        "shortDescription": { "text": description },
+        "defaultConfiguration": { "level": level(severity) },
+    })
+}
+
+fn result(path: &Path, error: &LintError, rule_index: usize) -> Value {
+    let mut physical_location = json!({
+        "artifactLocation": { "uri": artifact_uri(path) },
+    });
+    // A zero line means the error has no position in the file.
+    if error.span.start.line > 0 {
+        physical_location["region"] = json!({
+            "startLine": error.span.start.line,
+            "startColumn": error.span.start.column,
+            "endLine": error.span.end.line,
+            "endColumn": error.span.end.column,
+        });
+    }
+
+    json!({
+        "ruleId": error.rule,
+        "ruleIndex": rule_index,
+        "level": level(error.severity),
+        "message": { "text": error.message },
+        "locations": [{ "physicalLocation": physical_location }],
+    })
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+        Severity::Off => "none",
+    }
+}
+
+/// SARIF wants URI references: relative paths use forward slashes without a
+/// leading `./`, absolute ones become `file://` URIs.
+fn artifact_uri(path: &Path) -> String {
+    let uri = path.to_string_lossy().replace('\\', "/");
+    if path.is_absolute() {
+        let uri = uri.trim_start_matches('/');
+        format!("file:///{}", uri)
+    } else {
+        uri.strip_prefix("./").map(str::to_string).unwrap_or(uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check_file::Linter;
+    use crate::rules::check_property::load_known_props;
+    use crate::rules::check_value::load_known_values;
+
+    #[test]
+    fn counts_columns_in_code_points() {
+        let mut linter = Linter::new(
+            load_known_props().unwrap(),
+            load_known_values().unwrap(),
+            RuleRegistry::default(),
+            None,
+        )
+        .unwrap();
+        let report = linter.check_source(
+            Path::new("a.css"),
+            "/* 😀 */ .a { colour: red; }\n".to_string(),
+        );
+        let sarif = render(&[&report], &RuleRegistry::default()).unwrap();
+        let sarif: Value = serde_json::from_str(&sarif).unwrap();
+
+        let run = &sarif["runs"][0];
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+        let region = &run["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startColumn"], 14);
+    }
+}
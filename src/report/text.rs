@@ -0,0 +1,242 @@
+use crate::check_file::FileReport;
+use crate::config::Severity;
+use crate::lint_rules::LintError;
+use std::fmt::Write;
+
+/// Lines of source shown above and below the one a diagnostic points at.
+const CONTEXT_LINES: usize = 2;
+
+const TAB_WIDTH: usize = 4;
+
+/// Renders each diagnostic as a code frame: the offending line with a few
+/// lines around it and the span underlined. `color` adds ANSI styling.
+pub fn render(reports: &[&FileReport], color: bool) -> String {
+    let style = Style { color };
+    let mut out = String::new();
+
+    for report in reports {
+        let lines: Vec<&str> = report
+            .source
+            .as_deref()
+            .map(|source| source.lines().collect())
+            .unwrap_or_default();
+        // Rules report one after another, so put diagnostics back in source order.
+        let mut errors: Vec<&LintError> = report.errors.iter().collect();
+        errors.sort_by_key(|error| (error.span.start.line, error.span.start.column));
+        for error in errors {
+            render_error(&mut out, &style, report, &lines, error);
+            out.push('\n');
+        }
+    }
+
+    let error_count = count(reports, Severity::Error);
+    let warning_count = count(reports, Severity::Warning);
+    let _ = writeln!(
+        out,
+        "Checked {} file{}: {} error{}, {} warning{}",
+        reports.len(),
+        plural(reports.len()),
+        error_count,
+        plural(error_count),
+        warning_count,
+        plural(warning_count)
+    );
+
+    out
+}
+
+fn render_error(
+    out: &mut String,
+    style: &Style,
+    report: &FileReport,
+    lines: &[&str],
+    error: &LintError,
+) {
+    let severity_code = severity_code(error.severity);
+    let _ = writeln!(
+        out,
+        "{}{}",
+        style.paint(
+            severity_code,
+            &format!("{}[{}]", severity_label(error.severity), error.rule)
+        ),
+        style.paint("1", &format!(": {}", error.message))
+    );
+
+    let start = error.span.start;
+    let line_index = start.line.wrapping_sub(1);
+    let Some(line) = lines.get(line_index) else {
+        // Parse and IO failures may have no position, or no source to show.
+        let location = if start.line == 0 {
+            report.path.display().to_string()
+        } else {
+            format!("{}:{}:{}", report.path.display(), start.line, start.column)
+        };
+        let _ = writeln!(out, "  {} {}", style.paint("34", "-->"), location);
+        render_help(out, style, error, 1);
+        return;
+    };
+
+    let first = line_index.saturating_sub(CONTEXT_LINES);
+    let last = (line_index + CONTEXT_LINES).min(lines.len() - 1);
+    let width = (last + 1).to_string().len();
+    let gutter = style.paint("34", "|");
+
+    let _ = writeln!(
+        out,
+        "{:width$}{} {}:{}:{}",
+        "",
+        style.paint("34", "-->"),
+        report.path.display(),
+        start.line,
+        start.column
+    );
+    let _ = writeln!(out, "{:width$} {}", "", gutter);
+
+    for (index, text) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let number = style.paint("34", &format!("{:>width$}", index + 1));
+        let row = format!("{} {} {}", number, gutter, expand_tabs(text));
+        let _ = writeln!(out, "{}", row.trim_end());
+
+        if index == line_index {
+            let (indent, length) = underline(line, error);
+            let _ = writeln!(
+                out,
+                "{:width$} {} {}{}",
+                "",
+                gutter,
+                " ".repeat(indent),
+                style.paint(severity_code, &"^".repeat(length))
+            );
+        }
+    }
+
+    render_help(out, style, error, width);
+}
+
+fn render_help(out: &mut String, style: &Style, error: &LintError, width: usize) {
+    if let Some(fix) = &error.fix {
+        let _ = writeln!(
+            out,
+            "{:width$} {} {}",
+            "",
+            style.paint("34", "="),
+            style.paint("1", &format!("help: {}", fix))
+        );
+    }
+}
+
+/// Where the underline starts on `line` and how long it is, in display
+/// columns. Spans running past the end of the line are cut off there.
+fn underline(line: &str, error: &LintError) -> (usize, usize) {
+    let start = error.span.start;
+    let end = error.span.end;
+    let chars: Vec<char> = line.trim_end().chars().collect();
+
+    let from = start.column.saturating_sub(1).min(chars.len());
+    let to = if end.line == start.line {
+        end.column.saturating_sub(1).clamp(from, chars.len())
+    } else {
+        chars.len()
+    };
+
+    let indent = display_width(&chars[..from]);
+    let length = display_width(&chars[from..to]).max(1);
+    (indent, length)
+}
+
+fn display_width(chars: &[char]) -> usize {
+    chars
+        .iter()
+        .map(|&ch| if ch == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Off => "info",
+    }
+}
+
+/// The ANSI SGR parameters used for a severity: bold red, yellow or blue.
+fn severity_code(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "1;31",
+        Severity::Warning => "1;33",
+        Severity::Info | Severity::Off => "1;34",
+    }
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn count(reports: &[&FileReport], severity: Severity) -> usize {
+    reports
+        .iter()
+        .flat_map(|report| &report.errors)
+        .filter(|error| error.severity == severity)
+        .count()
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PrettystrictError;
+    use crate::source::{Location, Span};
+    use std::path::PathBuf;
+
+    fn error_at(line: usize, column: usize, message: &str) -> LintError {
+        let location = Location {
+            line,
+            column,
+            offset: 0,
+        };
+        LintError {
+            message: message.to_string(),
+            span: Span {
+                start: location,
+                end: location,
+            },
+            ..LintError::from(PrettystrictError::DuplicateProperty)
+        }
+    }
+
+    #[test]
+    fn renders_diagnostics_in_source_order() {
+        let report = FileReport {
+            path: PathBuf::from("a.css"),
+            source: Some(".a {\n  color: red;\n  margin: 0;\n}\n".to_string()),
+            errors: vec![
+                error_at(3, 3, "third"),
+                error_at(2, 10, "second"),
+                error_at(2, 3, "first"),
+            ],
+        };
+        let out = render(&[&report], false);
+        let first = out.find("first").unwrap();
+        let second = out.find("second").unwrap();
+        let third = out.find("third").unwrap();
+        assert!(first < second && second < third, "{}", out);
+    }
+}
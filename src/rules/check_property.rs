@@ -1,77 +1,411 @@
 use crate::error::PrettystrictError;
-use crate::lint_rules::LintError;
+use crate::lint_rules::{Context, LintError, LintRule};
+use crate::rules::font_face::{FONT_FACE_DESCRIPTORS, is_font_face, on_font_face};
+use crate::source::Span;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
 use std::fs;
 
 #[derive(Serialize, Deserialize)]
 pub struct Property {
-    pub(crate) name: String,
-    pub(crate) value: String,
+    /// The name as written, including any vendor prefix.
+    pub name: String,
+    /// The value as serialized by the parser, so equivalent values compare
+    /// equal.
+    pub value: String,
+    /// The value as written in the source, without `!important`.
+    #[serde(default)]
+    pub raw_value: String,
+    /// The vendor prefix of `name`, such as `-webkit-`.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub important: bool,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+impl Property {
+    /// The name without its vendor prefix.
+    pub fn unprefixed_name(&self) -> &str {
+        self.prefix
+            .as_deref()
+            .and_then(|prefix| self.name.strip_prefix(prefix))
+            .unwrap_or(&self.name)
+    }
+
+    /// Whether this is a `--custom` property, which takes any value.
+    pub fn is_custom(&self) -> bool {
+        self.name.starts_with("--")
+    }
 }
 #[derive(Serialize, Deserialize)]
 pub struct Rule {
     pub selector: String,
     pub declaration: Vec<Property>,
-    pub at_rule: Vec<String>,
+    /// The enclosing at-rules, outermost first; for an at-rule linted on its
+    /// own, such as `@font-face` or `@import`, the last entry is that rule.
+    pub at_rule: Vec<AtRule>,
+    /// How many blocks inside the outermost style rule enclose this rule's
+    /// block: 0 for a top-level rule, 1 for `&:hover` directly inside one.
+    #[serde(default)]
+    pub nesting_depth: usize,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// An at-rule, located by the span of its prelude.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtRule {
+    #[serde(flatten)]
+    pub kind: AtRuleKind,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// The kind of an at-rule and its parsed prelude.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum AtRuleKind {
+    Media {
+        query: String,
+    },
+    Supports {
+        condition: String,
+    },
+    Keyframes {
+        name: String,
+    },
+    /// A `@layer` block, with at most one name, or a statement listing the
+    /// layer order.
+    Layer {
+        names: Vec<String>,
+    },
+    Container {
+        name: Option<String>,
+        condition: String,
+    },
+    Scope {
+        start: Option<String>,
+        end: Option<String>,
+    },
+    StartingStyle,
+    FontFace,
+    Page {
+        selectors: Vec<String>,
+    },
+    /// A margin box inside `@page`, named with its `@`, such as `@top-left`.
+    PageMargin {
+        name: String,
+    },
+    Property {
+        name: String,
+    },
+    CounterStyle {
+        name: String,
+    },
+    Import {
+        url: String,
+        layer: Option<String>,
+        supports: Option<String>,
+        media: Option<String>,
+    },
+    Namespace {
+        prefix: Option<String>,
+        url: String,
+    },
+    /// Any other at-rule, named with its `@`, including unknown ones.
+    Other {
+        name: String,
+        prelude: String,
+    },
+}
+
+impl AtRuleKind {
+    /// The at-rule name, including the `@`.
+    pub fn name(&self) -> &str {
+        match self {
+            AtRuleKind::Media { .. } => "@media",
+            AtRuleKind::Supports { .. } => "@supports",
+            AtRuleKind::Keyframes { .. } => "@keyframes",
+            AtRuleKind::Layer { .. } => "@layer",
+            AtRuleKind::Container { .. } => "@container",
+            AtRuleKind::Scope { .. } => "@scope",
+            AtRuleKind::StartingStyle => "@starting-style",
+            AtRuleKind::FontFace => "@font-face",
+            AtRuleKind::Page { .. } => "@page",
+            AtRuleKind::Property { .. } => "@property",
+            AtRuleKind::CounterStyle { .. } => "@counter-style",
+            AtRuleKind::Import { .. } => "@import",
+            AtRuleKind::Namespace { .. } => "@namespace",
+            AtRuleKind::PageMargin { name } | AtRuleKind::Other { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for AtRuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut prelude = Vec::new();
+        match self {
+            AtRuleKind::Media { query } => prelude.push(query.clone()),
+            AtRuleKind::Supports { condition } => prelude.push(condition.clone()),
+            AtRuleKind::Keyframes { name }
+            | AtRuleKind::Property { name }
+            | AtRuleKind::CounterStyle { name } => prelude.push(name.clone()),
+            AtRuleKind::Layer { names } if !names.is_empty() => prelude.push(names.join(", ")),
+            AtRuleKind::Container { name, condition } => {
+                prelude.extend(name.clone());
+                prelude.push(condition.clone());
+            }
+            AtRuleKind::Scope { start, end } => {
+                prelude.extend(start.as_ref().map(|start| format!("({})", start)));
+                prelude.extend(end.as_ref().map(|end| format!("to ({})", end)));
+            }
+            AtRuleKind::Page { selectors } if !selectors.is_empty() => {
+                prelude.push(selectors.join(", "))
+            }
+            AtRuleKind::Import {
+                url,
+                layer,
+                supports,
+                media,
+            } => {
+                prelude.push(format!("{:?}", url));
+                prelude.extend(layer.as_ref().map(|layer| match layer.as_str() {
+                    "" => "layer".to_string(),
+                    layer => format!("layer({})", layer),
+                }));
+                prelude.extend(
+                    supports
+                        .as_ref()
+                        .map(|supports| format!("supports({})", supports)),
+                );
+                prelude.extend(media.clone());
+            }
+            AtRuleKind::Namespace { prefix, url } => {
+                prelude.extend(prefix.clone());
+                prelude.push(format!("{:?}", url));
+            }
+            AtRuleKind::Other { prelude: text, .. } if !text.is_empty() => {
+                prelude.push(text.clone())
+            }
+            _ => {}
+        }
+
+        write!(f, "{}", self.name())?;
+        for part in prelude {
+            write!(f, " {}", part)?;
+        }
+        Ok(())
+    }
 }
+
+/// At-rules whose blocks hold descriptors rather than properties.
+const DESCRIPTOR_AT_RULES: &[&str] = &[
+    "@font-face",
+    "@property",
+    "@counter-style",
+    "@font-palette-values",
+    "@font-feature-values",
+    "@view-transition",
+    "@viewport",
+];
+
+impl Rule {
+    /// The kinds of the enclosing at-rules, which identify the context a
+    /// rule applies in regardless of where each at-rule is written.
+    pub fn context(&self) -> Vec<&AtRuleKind> {
+        self.at_rule.iter().map(|at_rule| &at_rule.kind).collect()
+    }
+
+    /// Whether the declarations are descriptors of an at-rule such as
+    /// `@font-face`, which the property and value lists do not cover.
+    pub fn declares_descriptors(&self) -> bool {
+        self.at_rule
+            .last()
+            .is_some_and(|at_rule| DESCRIPTOR_AT_RULES.contains(&at_rule.kind.name()))
+    }
+}
+
+/// The property list compiled into the binary.
+const DEFAULT_PROPS: &str = include_str!("../CSS/Props.json");
+
 #[derive(Debug, Deserialize)]
 pub struct PropertyList {
+    #[serde(default)]
     pub properties: Vec<String>,
-    #[serde(rename = "at-rules")]
+    #[serde(rename = "at-rules", default)]
     pub at_rules: Vec<String>,
 }
-pub fn load_known_props(path: &str) -> Result<PropertyList, LintError> {
-    let json_content = fs::read_to_string(path).map_err(|e| LintError {
-        selector: "".into(),
-        property: "".into(),
-        message: e.to_string(),
-        kind: PrettystrictError::IoError(e),
-    })?;
-    let props: PropertyList = serde_json::from_str(&json_content).map_err(|e| LintError {
-        selector: "".into(),
-        property: "".into(),
-        message: e.to_string(),
-        kind: PrettystrictError::JsonError(e),
-    })?;
+
+impl PropertyList {
+    /// Adds the entries of `other` that are not already known.
+    pub fn extend(&mut self, other: PropertyList) {
+        for property in other.properties {
+            if !self.properties.contains(&property) {
+                self.properties.push(property);
+            }
+        }
+        for at_rule in other.at_rules {
+            if !self.at_rules.contains(&at_rule) {
+                self.at_rules.push(at_rule);
+            }
+        }
+    }
+}
+
+pub fn load_known_props() -> Result<PropertyList, LintError> {
+    parse_known_props(DEFAULT_PROPS)
+}
+
+pub fn load_known_props_from(path: &str) -> Result<PropertyList, LintError> {
+    let json_content =
+        fs::read_to_string(path).map_err(|e| LintError::from(PrettystrictError::IoError(e)))?;
+    parse_known_props(&json_content)
+}
+
+fn parse_known_props(json_content: &str) -> Result<PropertyList, LintError> {
+    let props: PropertyList =
+        serde_json::from_str(json_content).map_err(|e| LintError::from(PrettystrictError::JsonError(e)))?;
     Ok(props)
 }
 
 pub fn check_props(rule: &Rule, known_props: &PropertyList) -> Vec<LintError> {
     let mut errors = Vec::new();
+    if is_font_face(rule) {
+        for declaration in &rule.declaration {
+            if !FONT_FACE_DESCRIPTORS.contains(&declaration.name.as_str()) {
+                errors.push(on_font_face(LintError::new(
+                    rule,
+                    declaration,
+                    format!("{} is not a @font-face descriptor", declaration.name),
+                    PrettystrictError::UnknownProperty(declaration.name.clone()),
+                )));
+            }
+        }
+        return errors;
+    }
+    if rule.declares_descriptors() {
+        return errors;
+    }
 
-    for declaration in &rule.declaration {
-        if !known_props.properties.contains(&declaration.name) {
-            errors.push(LintError {
-                selector: rule.selector.clone(),
-                property: declaration.name.clone(),
-                message: format!("{} is unknown", declaration.name),
-                kind: PrettystrictError::UnknownProperty(declaration.name.clone()),
-            });
+    for declaration in rule.declaration.iter().filter(|d| !d.is_custom()) {
+        let name = declaration.unprefixed_name();
+        if !known_props.properties.iter().any(|known| known == name) {
+            errors.push(LintError::new(
+                rule,
+                declaration,
+                format!("{} is unknown", declaration.name),
+                PrettystrictError::UnknownProperty(declaration.name.clone()),
+            ));
         }
     }
 
     errors
 }
 
-pub fn check_at_rule(rule: &Rule, known_props: &PropertyList) -> Vec<LintError> {
+/// Reports every at-rule in the stylesheet whose name is not in the known
+/// at-rule list, once per at-rule however many rules it contains.
+pub fn check_at_rules(rules: &[Rule], known_props: &PropertyList) -> Vec<LintError> {
     let mut errors = Vec::new();
+    let mut checked = HashSet::new();
 
-    for at_rule in &rule.at_rule {
-        let at_rule_with_at = if at_rule.starts_with('@') {
-            at_rule.clone()
-        } else {
-            format!("@{}", at_rule)
-        };
-
-        if !known_props.at_rules.contains(&at_rule_with_at) {
+    for at_rule in rules.iter().flat_map(|rule| &rule.at_rule) {
+        if !checked.insert(at_rule.span.start.offset) {
+            continue;
+        }
+        let name = at_rule.kind.name();
+        if !known_props
+            .at_rules
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(name))
+        {
             errors.push(LintError {
                 selector: "".to_string(),
-                property: at_rule_with_at.clone(),
-                message: format!("Unknown at-rule: {}", at_rule_with_at),
-                kind: PrettystrictError::UnknownProperty(at_rule_with_at),
+                property: name.to_string(),
+                message: format!("Unknown at-rule: {}", name),
+                span: at_rule.span,
+                ..LintError::from(PrettystrictError::UnknownProperty(name.to_string()))
             });
         }
     }
 
     errors
 }
+
+pub struct NoUnknownProperty;
+
+impl LintRule for NoUnknownProperty {
+    fn id(&self) -> &'static str {
+        "no-unknown-property"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow properties missing from the known property list"
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        check_props(rule, context.known_props)
+    }
+}
+
+pub struct NoUnknownAtRule;
+
+impl LintRule for NoUnknownAtRule {
+    fn id(&self) -> &'static str {
+        "no-unknown-at-rule"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow at-rules missing from the known at-rule list"
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], context: &Context) -> Vec<LintError> {
+        check_at_rules(rules, context.known_props)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_css::parse_css;
+    use crate::testing::{lint, reported};
+
+    #[test]
+    fn records_the_parsed_prelude_of_each_at_rule() {
+        let source =
+            "@layer base {\n  @container card (width > 30em) {\n    .a { color: red }\n  }\n}\n";
+        let rules = parse_css(source).expect("test CSS parses");
+        assert_eq!(
+            rules[0].context(),
+            [
+                &AtRuleKind::Layer {
+                    names: vec!["base".to_string()]
+                },
+                &AtRuleKind::Container {
+                    name: Some("card".to_string()),
+                    condition: "(width > 30em)".to_string()
+                },
+            ]
+        );
+        assert_eq!(rules[0].at_rule[1].span.start.line, 2);
+    }
+
+    #[test]
+    fn accepts_known_at_rules_around_keyframes_and_margins() {
+        let source = "@keyframes fade {\n  from { opacity: 0 }\n  to { opacity: 1 }\n}\n\
+            @page {\n  @top-left { content: 'x' }\n}\n@media print {\n  .a { color: red }\n}\n";
+        assert!(lint(NoUnknownAtRule, source).is_empty());
+    }
+
+    #[test]
+    fn reports_an_unknown_at_rule_at_its_prelude() {
+        let source =
+            ".a { color: red }\n@frobnicate foo;\n@media print {\n  .b { color: red }\n}\n";
+        assert_eq!(
+            lint(NoUnknownAtRule, source),
+            reported("no-unknown-at-rule", &[2])
+        );
+    }
+}
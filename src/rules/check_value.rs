@@ -1,5 +1,5 @@
 use crate::error::PrettystrictError;
-use crate::lint_rules::LintError;
+use crate::lint_rules::{Context, LintError, LintRule};
 use crate::rules::check_property::Rule;
 use serde::Deserialize;
 use std::fs;
@@ -7,6 +7,9 @@ use std::fs;
 use regex::Regex;
 use std::collections::HashMap;
 
+/// The value rules compiled into the binary.
+const DEFAULT_VALUES: &str = include_str!("../CSS/Values.json");
+
 #[derive(Debug, Deserialize)]
 pub struct ValueList {
     #[serde(flatten)]
@@ -41,40 +44,77 @@ pub struct Range {
     max: f32,
 }
 
-pub fn load_known_values(path: &str) -> Result<ValueList, LintError> {
-    let json_value = fs::read_to_string(path).map_err(|e| LintError {
-        selector: "".into(),
-        property: "".into(),
-        message: e.to_string(),
-        kind: PrettystrictError::IoError(e),
-    })?;
-    let valuelist: ValueList = serde_json::from_str(&json_value).map_err(|e| LintError {
-        selector: "".into(),
-        property: "".into(),
-        message: e.to_string(),
-        kind: PrettystrictError::JsonError(e),
-    })?;
+impl ValueList {
+    /// Merges `other` into this list; its entries replace existing ones for
+    /// the same property or shorthand.
+    pub fn extend(&mut self, other: ValueList) {
+        self.properties.extend(other.properties);
+        if let Some(shorthands) = other.shorthands {
+            self.shorthands
+                .get_or_insert_with(HashMap::new)
+                .extend(shorthands);
+        }
+    }
+}
+
+pub fn load_known_values() -> Result<ValueList, LintError> {
+    parse_known_values(DEFAULT_VALUES)
+}
+
+pub fn load_known_values_from(path: &str) -> Result<ValueList, LintError> {
+    let json_value =
+        fs::read_to_string(path).map_err(|e| LintError::from(PrettystrictError::IoError(e)))?;
+    parse_known_values(&json_value)
+}
+
+fn parse_known_values(json_value: &str) -> Result<ValueList, LintError> {
+    let valuelist: ValueList =
+        serde_json::from_str(json_value).map_err(|e| LintError::from(PrettystrictError::JsonError(e)))?;
     Ok(valuelist)
 }
 
+/// Whether `value` is one of `allowed`, where `#RRGGBB` stands for any hex
+/// color and an entry such as `rgb()` for any call of that function.
+fn is_allowed(allowed: &[String], value: &str) -> bool {
+    allowed.iter().any(|entry| {
+        if entry == "#RRGGBB" {
+            value.len() == 7
+                && value.starts_with('#')
+                && value[1..].bytes().all(|b| b.is_ascii_hexdigit())
+        } else if let Some(function) = entry.strip_suffix("()") {
+            value
+                .strip_prefix(function)
+                .is_some_and(|rest| rest.starts_with('(') && rest.ends_with(')'))
+        } else {
+            entry.eq_ignore_ascii_case(value)
+        }
+    })
+}
+
+/// Checks the normalized value of each declaration, with keywords compared
+/// in lowercase; messages quote the value as the author wrote it.
 pub fn check_value(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
     let mut errors = Vec::new();
+    if rule.declares_descriptors() {
+        return errors;
+    }
     let value_map = &known_values.properties;
     let re = Regex::new(r"^([0-9]*\.?[0-9]+)([a-zA-Z%]+)$").unwrap();
 
-    for decl in &rule.declaration {
-        let value = &decl.value;
+    for decl in rule.declaration.iter().filter(|d| !d.is_custom()) {
+        let value = &decl.value.to_ascii_lowercase();
+        let raw_value = &decl.raw_value;
         let property = &decl.name;
 
         match value_map.get(property) {
             Some(ValueRule::AllowedValues { allowed }) => {
-                if !allowed.contains(value) {
-                    errors.push(LintError {
-                        selector: rule.selector.clone(),
-                        property: property.clone(),
-                        message: format!("‘{}’ is not an allowed value for {}", value, property),
-                        kind: PrettystrictError::UnknownValue(value.clone()),
-                    });
+                if !is_allowed(allowed, value) {
+                    errors.push(LintError::new(
+                        rule,
+                        decl,
+                        format!("‘{}’ is not an allowed value for {}", raw_value, property),
+                        PrettystrictError::UnknownValue(raw_value.clone()),
+                    ));
                 }
             }
 
@@ -94,32 +134,32 @@ pub fn check_value(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
                         // valid value
                     }
                     _ => {
-                        errors.push(LintError {
-                            selector: rule.selector.clone(),
-                            property: property.clone(),
-                            message: format!(
+                        errors.push(LintError::new(
+                            rule,
+                            decl,
+                            format!(
                                 "‘{}’ is not a valid unit/range for {}",
-                                value, property
+                                raw_value, property
                             ),
-                            kind: PrettystrictError::UnknownValue(value.clone()),
-                        });
+                            PrettystrictError::UnknownValue(raw_value.clone()),
+                        ));
                     }
                 }
             }
 
             Some(ValueRule::KeywordGroup { keywords }) => {
                 if property == "position" {
-                    if let Some(keyword_rule) = keywords.get(value) {
+                    if let Some(keyword_rule) = keywords.get(value.as_str()) {
                         if let Some(_allowed) = &keyword_rule.allowed {
                             // (Optional: validate something if needed
                         }
                     } else {
-                        errors.push(LintError {
-                            selector: rule.selector.clone(),
-                            property: property.clone(),
-                            message: format!("Invalid value for position: '{}'", value),
-                            kind: PrettystrictError::UnknownValue(value.clone()),
-                        });
+                        errors.push(LintError::new(
+                            rule,
+                            decl,
+                            format!("Invalid value for position: '{}'", raw_value),
+                            PrettystrictError::UnknownValue(raw_value.clone()),
+                        ));
                     }
                 }
 
@@ -127,43 +167,101 @@ pub fn check_value(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
                     .declaration
                     .iter()
                     .find(|d| d.name == "position")
-                    .map(|d| d.value.as_str());
+                    .map(|d| d.value.to_ascii_lowercase());
 
-                if let Some("static") = position_value {
-                    if let Some(ValueRule::KeywordGroup { keywords }) =
+                if position_value.as_deref() == Some("static")
+                    && let Some(ValueRule::KeywordGroup { keywords }) =
                         known_values.properties.get("position")
-                    {
-                        if let Some(static_rule) = keywords.get("static") {
-                            if let Some(ignores) = &static_rule.ignores {
-                                for decl in &rule.declaration {
-                                    if ignores.contains(&decl.name) {
-                                        errors.push(LintError {
-                                            selector: rule.selector.clone(),
-                                            property: property.clone(),
-                                            message: format!(
-                                                "'{}' is not valid for static.",
-                                                decl.name
-                                            ),
-                                            kind: PrettystrictError::UnknownValue(value.clone()),
-                                        });
-                                    }
-                                }
-                            }
+                    && let Some(static_rule) = keywords.get("static")
+                    && let Some(ignores) = &static_rule.ignores
+                {
+                    for decl in &rule.declaration {
+                        if ignores.contains(&decl.name) {
+                            errors.push(LintError::new(
+                                rule,
+                                decl,
+                                format!("'{}' is not valid for static.", decl.name),
+                                PrettystrictError::UnknownValue(raw_value.clone()),
+                            ));
                         }
                     }
                 }
             }
 
             None => {
-                errors.push(LintError {
-                    selector: rule.selector.clone(),
-                    property: property.clone(),
-                    message: format!("No known values defined for '{}'", property),
-                    kind: PrettystrictError::UnknownValue(value.clone()),
-                });
+                errors.push(LintError::new(
+                    rule,
+                    decl,
+                    format!("No known values defined for '{}'", property),
+                    PrettystrictError::UnknownValue(raw_value.clone()),
+                ));
             }
         }
     }
 
     errors
 }
+
+pub struct NoUnknownValue;
+
+impl LintRule for NoUnknownValue {
+    fn id(&self) -> &'static str {
+        "no-unknown-value"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow values outside those allowed for a property"
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        check_value(rule, context.known_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_css::parse_css;
+
+    fn messages(source: &str) -> Vec<String> {
+        let known_values = load_known_values().unwrap();
+        let rules = parse_css(source).expect("test CSS parses");
+        check_value(&rules[0], &known_values)
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_keywords_in_any_case() {
+        assert!(messages(".a { display: Flex; text-align: CENTER; }").is_empty());
+    }
+
+    #[test]
+    fn quotes_unknown_values_as_written() {
+        assert_eq!(
+            messages(".a { display: Flexy; }"),
+            ["‘Flexy’ is not an allowed value for display"]
+        );
+    }
+
+    #[test]
+    fn checks_colors_in_their_normalized_form() {
+        // Named colors are serialized as hex, which `#RRGGBB` allows.
+        assert!(messages(".a { color: Red; background-color: #ABC; }").is_empty());
+        // Translucent colors become `rgba()`, which is not in the list, but
+        // the message still shows the author's text.
+        assert_eq!(
+            messages(".a { color: rgb(0 0 0 / 50%); }"),
+            ["‘rgb(0 0 0 / 50%)’ is not an allowed value for color"]
+        );
+    }
+
+    #[test]
+    fn reads_the_position_keyword_in_any_case() {
+        assert_eq!(
+            messages(".a { position: Static; top: 1px; }"),
+            ["'top' is not valid for static."]
+        );
+    }
+}
@@ -1,113 +1,259 @@
-use super::check_property::Rule;
+use super::check_property::{Property, Rule};
 use crate::error::PrettystrictError;
-use crate::lint_rules::LintError;
-use crate::rules::check_value::ValueList;
-use std::collections::{HashMap, HashSet};
+use crate::fix::{move_declaration, remove_declaration};
+use crate::lint_rules::{Context, LintError, LintRule, OptionKind, RuleOption};
+use std::collections::HashMap;
 
-pub struct Location {
-    pub(crate) line: usize,
-    pub(crate) column: usize,
-}
-pub fn duplicate_declaration(rule: &Rule, _location: &Location) -> Vec<LintError> {
+/// The order `check_order` enforces unless the config provides one.
+pub const DEFAULT_ORDER: [&str; 9] = [
+    "display",
+    "position",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "z-index",
+    "color",
+    "background",
+];
+
+pub fn duplicate_declaration(rule: &Rule, source: &str) -> Vec<LintError> {
     let mut errors = Vec::new();
-    let mut seen: HashSet<&str> = HashSet::new();
+    let mut seen: HashMap<&str, &Property> = HashMap::new();
     for declaration in &rule.declaration {
-        if !seen.insert(declaration.name.as_str()) {
+        if let Some(earlier) = seen.insert(declaration.name.as_str(), declaration) {
+            // A different value is usually a fallback for browsers that do
+            // not understand the later one, so only exact repeats are
+            // removed automatically.
+            let redundant =
+                earlier.value == declaration.value && earlier.important == declaration.important;
+            // An earlier `!important` declaration wins over this one, so it
+            // is this one that has no effect.
+            let fix = if earlier.important && !declaration.important {
+                format!("remove this '{}' declaration", declaration.name)
+            } else {
+                format!("remove the earlier '{}' declaration", declaration.name)
+            };
             errors.push(LintError {
-                selector: rule.selector.clone(),
-                property: declaration.name.clone(),
-                message: "<- duplicate property found.".to_string(),
-                kind: PrettystrictError::DuplicateProperty,
+                fix: Some(fix),
+                edits: if redundant {
+                    remove_declaration(source, earlier).into_iter().collect()
+                } else {
+                    Vec::new()
+                },
+                ..LintError::new(
+                    rule,
+                    declaration,
+                    "<- duplicate property found.".to_string(),
+                    PrettystrictError::DuplicateProperty,
+                )
             });
         }
     }
     errors
 }
 
-pub fn shorthand_detection(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
+pub fn shorthand_detection(
+    rule: &Rule,
+    shorthand_map: &HashMap<String, Vec<String>>,
+    source: &str,
+) -> Vec<LintError> {
     let mut errors = Vec::new();
-    let mut seen_props = HashSet::new();
-
-    let shorthand_map = match &known_values.shorthands {
-        Some(map) => map,
-        None => return errors,
-    };
+    let mut seen_props: HashMap<String, &Property> = HashMap::new();
 
     for decl in &rule.declaration {
         let prop = decl.name.as_str();
 
         for (shorthand, longhands) in shorthand_map {
-            if longhands.contains(&prop.to_string()) && seen_props.contains(shorthand) {
+            // A later normal declaration never overrides an important one.
+            let overrides = |earlier: &Property| decl.important || !earlier.important;
+
+            if longhands.contains(&prop.to_string())
+                && seen_props
+                    .get(shorthand)
+                    .is_some_and(|earlier| overrides(earlier))
+            {
                 errors.push(LintError {
-                    selector: rule.selector.clone(),
-                    property: decl.name.clone(),
-                    message: format!(
-                        "'{}' overrides previously defined shorthand '{}'",
-                        prop, shorthand
-                    ),
-                    kind: PrettystrictError::ProperyOverride,
+                    fix: Some(format!("fold '{}' into the '{}' value", prop, shorthand)),
+                    ..LintError::new(
+                        rule,
+                        decl,
+                        format!(
+                            "'{}' overrides previously defined shorthand '{}'",
+                            prop, shorthand
+                        ),
+                        PrettystrictError::ProperyOverride,
+                    )
                 });
             }
 
             if shorthand == prop {
                 for longhand in longhands {
-                    if seen_props.contains(longhand) {
+                    if let Some(earlier) = seen_props.get(longhand)
+                        && overrides(earlier)
+                    {
                         errors.push(LintError {
-                            selector: rule.selector.clone(),
-                            property: decl.name.clone(),
-                            message: format!(
-                                "'{}' overrides previously defined longhand '{}'",
-                                shorthand, longhand
-                            ),
-                            kind: PrettystrictError::ProperyOverride,
+                            fix: Some(format!("remove the earlier '{}' declaration", longhand)),
+                            // The shorthand resets the longhand, so dropping
+                            // it does not change the result.
+                            edits: remove_declaration(source, earlier).into_iter().collect(),
+                            ..LintError::new(
+                                rule,
+                                decl,
+                                format!(
+                                    "'{}' overrides previously defined longhand '{}'",
+                                    shorthand, longhand
+                                ),
+                                PrettystrictError::ProperyOverride,
+                            )
                         });
                     }
                 }
             }
         }
 
-        seen_props.insert(prop.to_string());
+        seen_props.insert(prop.to_string(), decl);
     }
 
     errors
 }
-pub fn check_order(rule: &Rule) -> Vec<LintError> {
+pub fn check_order(rule: &Rule, prefered_order: &[String], source: &str) -> Vec<LintError> {
     let mut errors = Vec::new();
 
-    let prefered_order = vec![
-        "display",
-        "position",
-        "top",
-        "right",
-        "bottom",
-        "left",
-        "z-index",
-        "color",
-        "background",
-    ];
-
     let order_map: HashMap<&str, usize> = prefered_order
         .iter()
         .enumerate()
-        .map(|(i, &prop)| (prop, i))
+        .map(|(i, prop)| (prop.as_str(), i))
         .collect();
 
     let mut last_index = 0usize;
-    let mut first = true;
+    let mut last: Option<&Property> = None;
 
     for prop in &rule.declaration {
         if let Some(&current_index) = order_map.get(&prop.name.as_str()) {
-            if !first && current_index < last_index {
+            if let Some(last) = last
+                && current_index < last_index
+            {
                 errors.push(LintError {
-                    selector: rule.selector.clone(),
-                    property: prop.name.clone(),
-                    message: "invalid property order found.".to_string(),
-                    kind: PrettystrictError::ProperyOverride,
+                    fix: Some(format!("move '{}' before '{}'", prop.name, last.name)),
+                    edits: move_declaration(source, prop, last).unwrap_or_default(),
+                    ..LintError::new(
+                        rule,
+                        prop,
+                        "invalid property order found.".to_string(),
+                        PrettystrictError::PropertyOrder,
+                    )
                 });
             }
             last_index = current_index;
-            first = false;
+            last = Some(prop);
         }
     }
     errors
 }
+
+pub struct NoDuplicateDeclarations;
+
+impl LintRule for NoDuplicateDeclarations {
+    fn id(&self) -> &'static str {
+        "no-duplicate-declarations"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow declaring the same property twice in a rule"
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        duplicate_declaration(rule, context.source)
+    }
+}
+
+pub struct NoShorthandOverride;
+
+impl LintRule for NoShorthandOverride {
+    fn id(&self) -> &'static str {
+        "no-shorthand-override"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow shorthands and longhands overriding each other"
+    }
+
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "shorthands",
+            kind: OptionKind::StringListMap,
+        }]
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        let shorthands: HashMap<String, Vec<String>> = context
+            .settings
+            .option(self.id(), "shorthands")
+            .or_else(|| context.known_values.shorthands.clone())
+            .unwrap_or_default();
+        shorthand_detection(rule, &shorthands, context.source)
+    }
+}
+
+pub struct PropertyOrder;
+
+impl LintRule for PropertyOrder {
+    fn id(&self) -> &'static str {
+        "property-order"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require declarations to follow the preferred property order"
+    }
+
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "order",
+            kind: OptionKind::StringList,
+        }]
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        let order: Vec<String> = context
+            .settings
+            .option(self.id(), "order")
+            .unwrap_or_else(|| DEFAULT_ORDER.iter().map(|p| p.to_string()).collect());
+        check_order(rule, &order, context.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fix::apply_fixes;
+    use crate::parse_css::parse_css;
+
+    fn fix_duplicates(source: &str) -> String {
+        let rules = parse_css(source).expect("test CSS parses");
+        let errors = duplicate_declaration(&rules[0], source);
+        apply_fixes(source, &errors).0
+    }
+
+    #[test]
+    fn removes_an_exact_repeat() {
+        assert_eq!(
+            fix_duplicates(".a { color: red; color: red; }"),
+            ".a { color: red; }"
+        );
+    }
+
+    #[test]
+    fn keeps_fallback_values() {
+        let source = ".a { display: -webkit-box; display: flex; }";
+        assert_eq!(fix_duplicates(source), source);
+        let source = ".a { background: red; background: linear-gradient(red, blue); }";
+        assert_eq!(fix_duplicates(source), source);
+    }
+
+    #[test]
+    fn keeps_an_earlier_important_declaration() {
+        let source = ".a { color: blue !important; color: red; }";
+        assert_eq!(fix_duplicates(source), source);
+    }
+}
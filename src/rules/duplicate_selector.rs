@@ -0,0 +1,167 @@
+use super::check_property::{AtRuleKind, Rule};
+use crate::config::Severity;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{Context, LintError, LintRule};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// Whether `rule` is a selector-based rule rather than a keyframe or a
+/// descriptor block such as `@font-face`.
+pub fn is_style_rule(rule: &Rule) -> bool {
+    !rule.selector.is_empty()
+        && !rule
+            .at_rule
+            .iter()
+            .any(|a| matches!(a.kind, AtRuleKind::Keyframes { .. }))
+}
+
+pub fn duplicate_selectors(rules: &[Rule]) -> Vec<LintError> {
+    let mut errors = Vec::new();
+    let mut seen: HashMap<(Vec<&AtRuleKind>, &str), &Rule> = HashMap::new();
+
+    for rule in rules.iter().filter(|rule| is_style_rule(rule)) {
+        match seen.entry((rule.context(), rule.selector.as_str())) {
+            Entry::Occupied(first) => {
+                let line = first.get().span.start.line;
+                errors.push(LintError {
+                    fix: Some(format!("merge it into the rule at line {}", line)),
+                    ..LintError::for_rule(
+                        rule,
+                        format!("selector '{}' is already used at line {}", rule.selector, line),
+                        PrettystrictError::DuplicateSelector,
+                    )
+                });
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(rule);
+            }
+        }
+    }
+
+    errors
+}
+
+/// The at-rules a block sits in plus its `(name, value, important)` declarations.
+type BlockKey<'a> = (Vec<&'a AtRuleKind>, Vec<(&'a str, &'a str, bool)>);
+
+pub fn duplicate_blocks(rules: &[Rule]) -> Vec<LintError> {
+    let mut errors = Vec::new();
+    let mut seen: HashMap<BlockKey, &Rule> = HashMap::new();
+
+    for rule in rules.iter().filter(|rule| is_style_rule(rule)) {
+        // A single declaration in common is too little to be worth merging.
+        if rule.declaration.len() < 2 {
+            continue;
+        }
+        let declarations = rule
+            .declaration
+            .iter()
+            .map(|d| (d.name.as_str(), d.value.as_str(), d.important))
+            .collect();
+
+        match seen.entry((rule.context(), declarations)) {
+            Entry::Occupied(first) if first.get().selector != rule.selector => {
+                let first = first.get();
+                errors.push(LintError {
+                    fix: Some(format!(
+                        "combine the selectors as '{}, {}'",
+                        first.selector, rule.selector
+                    )),
+                    ..LintError::for_rule(
+                        rule,
+                        format!(
+                            "declarations are identical to those of '{}' at line {}",
+                            first.selector, first.span.start.line
+                        ),
+                        PrettystrictError::DuplicateBlock,
+                    )
+                });
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(rule);
+            }
+        }
+    }
+
+    errors
+}
+
+pub struct NoDuplicateSelectors;
+
+impl LintRule for NoDuplicateSelectors {
+    fn id(&self) -> &'static str {
+        "no-duplicate-selectors"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow repeating a selector within the same at-rule"
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        duplicate_selectors(rules)
+    }
+}
+
+pub struct NoDuplicateDeclarationBlocks;
+
+impl LintRule for NoDuplicateDeclarationBlocks {
+    fn id(&self) -> &'static str {
+        "no-duplicate-declaration-blocks"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow copying the same declarations under different selectors"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        duplicate_blocks(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{lint, reported};
+
+    #[test]
+    fn reports_a_repeated_selector() {
+        let source = ".a { color: red; }\n.b { color: blue; }\n.a { margin: 0; }\n";
+        assert_eq!(
+            lint(NoDuplicateSelectors, source),
+            reported("no-duplicate-selectors", &[3])
+        );
+    }
+
+    #[test]
+    fn allows_the_same_selector_in_another_context() {
+        let source = ".a { color: red; }\n@media (min-width: 40em) { .a { color: blue; } }\n";
+        assert!(lint(NoDuplicateSelectors, source).is_empty());
+    }
+
+    #[test]
+    fn ignores_keyframes_and_descriptor_blocks() {
+        let source = "@keyframes a { from { opacity: 0; } from { opacity: 1; } }\n\
+            @font-face { font-family: A; }\n@font-face { font-family: B; }\n";
+        assert!(lint(NoDuplicateSelectors, source).is_empty());
+    }
+
+    #[test]
+    fn reports_identical_declaration_blocks() {
+        let source = ".a { color: red; margin: 0; }\n.b { color: red; margin: 0; }\n";
+        assert_eq!(
+            lint(NoDuplicateDeclarationBlocks, source),
+            reported("no-duplicate-declaration-blocks", &[2])
+        );
+    }
+
+    #[test]
+    fn tells_blocks_apart_by_importance() {
+        let source = ".a { color: red !important; margin: 0; }\n.b { color: red; margin: 0; }\n";
+        assert!(lint(NoDuplicateDeclarationBlocks, source).is_empty());
+    }
+}
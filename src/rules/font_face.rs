@@ -0,0 +1,268 @@
+use super::check_property::{AtRuleKind, Property, Rule};
+use crate::config::Severity;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{Context, LintError, LintRule};
+use crate::source::split_top_level_commas;
+
+/// The descriptors `@font-face` accepts.
+pub const FONT_FACE_DESCRIPTORS: &[&str] = &[
+    "font-family",
+    "src",
+    "font-style",
+    "font-weight",
+    "font-stretch",
+    "font-display",
+    "unicode-range",
+    "font-feature-settings",
+    "font-variation-settings",
+    "font-language-override",
+    "font-named-instance",
+    "ascent-override",
+    "descent-override",
+    "line-gap-override",
+    "size-adjust",
+];
+
+/// Whether `rule` is the descriptor block of a `@font-face`.
+pub fn is_font_face(rule: &Rule) -> bool {
+    rule.at_rule
+        .last()
+        .is_some_and(|at_rule| at_rule.kind == AtRuleKind::FontFace)
+}
+
+fn descriptor<'a>(rule: &'a Rule, name: &str) -> Option<&'a Property> {
+    rule.declaration
+        .iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name))
+}
+
+/// Names the `@font-face` block as the selector of `error`, since the block
+/// has no selector of its own.
+pub fn on_font_face(error: LintError) -> LintError {
+    LintError {
+        selector: "@font-face".to_string(),
+        ..error
+    }
+}
+
+pub fn missing_descriptors(rule: &Rule, required: &[&str]) -> Vec<LintError> {
+    if !is_font_face(rule) {
+        return Vec::new();
+    }
+
+    required
+        .iter()
+        .filter(|name| descriptor(rule, name).is_none())
+        .map(|name| {
+            on_font_face(LintError::for_rule(
+                rule,
+                format!("@font-face is missing the '{}' descriptor", name),
+                PrettystrictError::MissingDescriptor(name.to_string()),
+            ))
+        })
+        .collect()
+}
+
+/// The format a font file's extension implies, for suggesting a hint.
+fn format_for_url(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    match extension.as_str() {
+        "woff2" => Some("woff2"),
+        "woff" => Some("woff"),
+        "ttf" => Some("truetype"),
+        "otf" => Some("opentype"),
+        "eot" => Some("embedded-opentype"),
+        "svg" => Some("svg"),
+        _ => None,
+    }
+}
+
+/// A `url()` entry of a `src` descriptor.
+struct UrlSource<'a> {
+    url: &'a str,
+    format: Option<String>,
+}
+
+/// The `url()` entries of a `src` value; `local()` entries are skipped.
+fn url_sources(value: &str) -> Vec<UrlSource<'_>> {
+    let mut sources = Vec::new();
+    for source in split_top_level_commas(value) {
+        let Some(rest) = source.strip_prefix("url(") else {
+            continue;
+        };
+        let Some(end) = rest.find(')') else {
+            continue;
+        };
+        let url = rest[..end].trim().trim_matches(['"', '\'']);
+        let format = rest[end + 1..].trim().strip_prefix("format(").map(|hint| {
+            let hint = hint.split(')').next().unwrap_or_default();
+            hint.trim().trim_matches(['"', '\'']).to_ascii_lowercase()
+        });
+        sources.push(UrlSource { url, format });
+    }
+    sources
+}
+
+pub fn src_formats(rule: &Rule) -> Vec<LintError> {
+    let mut errors = Vec::new();
+    if !is_font_face(rule) {
+        return errors;
+    }
+    let Some(src) = descriptor(rule, "src") else {
+        return errors;
+    };
+
+    let sources = url_sources(&src.value);
+    for source in sources.iter().filter(|source| source.format.is_none()) {
+        errors.push(LintError {
+            fix: format_for_url(source.url).map(|format| format!("add format(\"{}\")", format)),
+            ..on_font_face(LintError::new(
+                rule,
+                src,
+                format!("source '{}' has no format() hint", source.url),
+                PrettystrictError::FontFormat,
+            ))
+        });
+    }
+
+    let is_woff2 = |source: &UrlSource| source.format.as_deref() == Some("woff2");
+    if let Some(first) = sources.first()
+        && !is_woff2(first)
+        && let Some(woff2) = sources.iter().find(|source| is_woff2(source))
+    {
+        errors.push(LintError {
+            fix: Some(format!("move '{}' to the front of the list", woff2.url)),
+            ..on_font_face(LintError::new(
+                rule,
+                src,
+                format!(
+                    "the woff2 source '{}' should come before '{}'",
+                    woff2.url, first.url
+                ),
+                PrettystrictError::FontFormat,
+            ))
+        });
+    }
+
+    errors
+}
+
+pub struct FontFaceRequireDisplay;
+
+impl LintRule for FontFaceRequireDisplay {
+    fn id(&self) -> &'static str {
+        "font-face-require-display"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require font-display in @font-face"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, rule: &Rule, _context: &Context) -> Vec<LintError> {
+        missing_descriptors(rule, &["font-display"])
+    }
+}
+
+pub struct FontFaceSrcFormat;
+
+impl LintRule for FontFaceSrcFormat {
+    fn id(&self) -> &'static str {
+        "font-face-src-format"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require format() hints in @font-face src, with woff2 listed first"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, rule: &Rule, _context: &Context) -> Vec<LintError> {
+        src_formats(rule)
+    }
+}
+
+pub struct FontFaceNoMissingDescriptors;
+
+impl LintRule for FontFaceNoMissingDescriptors {
+    fn id(&self) -> &'static str {
+        "font-face-no-missing-descriptors"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require font-family and src in @font-face"
+    }
+
+    fn check(&self, rule: &Rule, _context: &Context) -> Vec<LintError> {
+        missing_descriptors(rule, &["font-family", "src"])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_css::parse_css;
+    use crate::testing::{lint, reported};
+
+    /// The fixes `src_formats` suggests for the first `@font-face` in `source`.
+    fn src_fixes(source: &str) -> Vec<Option<String>> {
+        let rules = parse_css(source).expect("test CSS parses");
+        src_formats(&rules[0])
+            .into_iter()
+            .map(|error| error.fix)
+            .collect()
+    }
+
+    #[test]
+    fn requires_family_and_src() {
+        let source = "@font-face {\n  font-family: A;\n}\n.a { color: red; }\n\
+            @font-face {\n  src: url(b.woff2) format(\"woff2\");\n}\n";
+        assert_eq!(
+            lint(FontFaceNoMissingDescriptors, source),
+            reported("font-face-no-missing-descriptors", &[1, 5])
+        );
+    }
+
+    #[test]
+    fn requires_font_display() {
+        let source = "@font-face {\n  font-family: A;\n  font-display: swap;\n}\n\
+            @font-face {\n  font-family: B;\n}\n";
+        assert_eq!(
+            lint(FontFaceRequireDisplay, source),
+            reported("font-face-require-display", &[5])
+        );
+    }
+
+    #[test]
+    fn reports_sources_without_a_format_hint() {
+        let source = "@font-face {\n  font-family: A;\n  src: local(A),\n    \
+            url(a.woff2) format(\"woff2\"),\n    url(a.ttf?v=1);\n}\n";
+        assert_eq!(
+            lint(FontFaceSrcFormat, source),
+            reported("font-face-src-format", &[3])
+        );
+        assert_eq!(
+            src_fixes(source),
+            [Some("add format(\"truetype\")".to_string())]
+        );
+    }
+
+    #[test]
+    fn wants_woff2_listed_first() {
+        let source = "@font-face {\n  font-family: A;\n  \
+            src: url(a.woff) format(\"woff\"), url(a.woff2) format(\"woff2\");\n}\n";
+        assert_eq!(
+            src_fixes(source),
+            [Some("move 'a.woff2' to the front of the list".to_string())]
+        );
+        let source = "@font-face {\n  font-family: A;\n  \
+            src: url(a.woff2) format(\"woff2\"), url(a.woff) format(\"woff\");\n}\n";
+        assert!(lint(FontFaceSrcFormat, source).is_empty());
+    }
+}
@@ -0,0 +1,535 @@
+use super::check_property::{AtRule, AtRuleKind, Rule};
+use crate::config::Severity;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{Context, LintError, LintRule};
+use std::collections::{BTreeMap, HashSet};
+
+/// Values of `animation-name` that never refer to keyframes.
+const RESERVED_NAMES: [&str; 6] = ["none", "initial", "inherit", "unset", "revert", "revert-layer"];
+
+/// Properties that cannot be animated, so browsers ignore them inside
+/// keyframes, besides the `animation-*` and `transition-*` ones.
+const NOT_ANIMATABLE: [&str; 3] = ["direction", "unicode-bidi", "will-change"];
+
+/// The `@keyframes` block `rule` is a keyframe of.
+fn keyframes_at_rule(rule: &Rule) -> Option<&AtRule> {
+    rule.at_rule
+        .iter()
+        .rev()
+        .find(|at_rule| matches!(at_rule.kind, AtRuleKind::Keyframes { .. }))
+}
+
+/// The name of the `@keyframes` block `rule` is a keyframe of.
+pub fn keyframes_name(rule: &Rule) -> Option<&str> {
+    keyframes_at_rule(rule).and_then(|at_rule| match &at_rule.kind {
+        AtRuleKind::Keyframes { name } => Some(name.as_str()),
+        _ => None,
+    })
+}
+
+/// The keyframes of each `@keyframes` block, in source order.
+fn keyframe_blocks(rules: &[Rule]) -> Vec<Vec<&Rule>> {
+    let mut blocks: Vec<(usize, Vec<&Rule>)> = Vec::new();
+    for rule in rules {
+        let Some(at_rule) = keyframes_at_rule(rule) else {
+            continue;
+        };
+        let offset = at_rule.span.start.offset;
+        match blocks.last_mut() {
+            Some((start, block)) if *start == offset => block.push(rule),
+            _ => blocks.push((offset, vec![rule])),
+        }
+    }
+    blocks.into_iter().map(|(_, block)| block).collect()
+}
+
+/// The parts of a keyframe selector with their offsets in percent; parts
+/// that are not `from`, `to` or a percentage, such as the timeline range
+/// `entry 10%`, have none.
+fn keyframe_offsets(selector: &str) -> Vec<(&str, Option<f64>)> {
+    selector
+        .split(',')
+        .map(str::trim)
+        .map(|part| {
+            let offset = match part.to_ascii_lowercase().as_str() {
+                "from" => Some(0.0),
+                "to" => Some(100.0),
+                part => part.strip_suffix('%').and_then(|n| n.parse().ok()),
+            };
+            (part, offset)
+        })
+        .collect()
+}
+
+/// Whether a keyframe can set `name`; keyframes accept the animation's
+/// timing function and composition even though neither is animatable.
+fn is_animatable(name: &str) -> bool {
+    match name {
+        "animation-timing-function" | "animation-composition" => true,
+        name => {
+            !name.starts_with("animation")
+                && !name.starts_with("transition")
+                && !NOT_ANIMATABLE.contains(&name)
+        }
+    }
+}
+
+/// Every keyframes name defined in the stylesheet, with its first keyframe.
+fn defined_keyframes(rules: &[Rule]) -> BTreeMap<&str, &Rule> {
+    let mut defined = BTreeMap::new();
+    for rule in rules {
+        if let Some(name) = keyframes_name(rule) {
+            defined.entry(name).or_insert(rule);
+        }
+    }
+    defined
+}
+
+/// The names listed in an `animation-name` value.
+fn animation_names(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(|name| name.trim().trim_matches(['"', '\'']))
+}
+
+pub fn unused_keyframes(rules: &[Rule]) -> Vec<LintError> {
+    let mut used = HashSet::new();
+    for declaration in rules.iter().flat_map(|rule| &rule.declaration) {
+        match declaration.name.as_str() {
+            "animation-name" => used.extend(animation_names(&declaration.value)),
+            // The name can sit anywhere in the shorthand, so any word counts.
+            "animation" => used.extend(
+                declaration
+                    .value
+                    .split([',', ' '])
+                    .map(|word| word.trim_matches(['"', '\''])),
+            ),
+            _ => {}
+        }
+    }
+
+    defined_keyframes(rules)
+        .into_iter()
+        .filter(|(name, _)| !used.contains(name))
+        .map(|(name, rule)| LintError {
+            selector: format!("@keyframes {}", name),
+            ..LintError::for_rule(
+                rule,
+                format!("keyframes '{}' are never used in an animation", name),
+                PrettystrictError::UnusedKeyframes(name.to_string()),
+            )
+        })
+        .collect()
+}
+
+pub fn undefined_keyframes(rules: &[Rule]) -> Vec<LintError> {
+    let defined = defined_keyframes(rules);
+    let mut errors = Vec::new();
+
+    for rule in rules {
+        for declaration in rule.declaration.iter().filter(|d| d.name == "animation-name") {
+            for name in animation_names(&declaration.value) {
+                if RESERVED_NAMES.contains(&name) || defined.contains_key(name) {
+                    continue;
+                }
+                errors.push(LintError::new(
+                    rule,
+                    declaration,
+                    format!("no @keyframes named '{}' is defined", name),
+                    PrettystrictError::UndefinedKeyframes(name.to_string()),
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+pub fn keyframe_selector_range(rule: &Rule) -> Vec<LintError> {
+    if keyframes_at_rule(rule).is_none() {
+        return Vec::new();
+    }
+
+    keyframe_offsets(&rule.selector)
+        .into_iter()
+        .filter(|(_, offset)| offset.is_some_and(|offset| !(0.0..=100.0).contains(&offset)))
+        .map(|(part, _)| {
+            LintError::for_rule(
+                rule,
+                format!("keyframe selector '{}' is outside 0% to 100%", part),
+                PrettystrictError::KeyframeOutOfRange(part.to_string()),
+            )
+        })
+        .collect()
+}
+
+pub fn duplicate_keyframe_offsets(rules: &[Rule]) -> Vec<LintError> {
+    let mut errors = Vec::new();
+
+    for block in keyframe_blocks(rules) {
+        let mut seen: Vec<(f64, &Rule)> = Vec::new();
+        for rule in block {
+            for (part, offset) in keyframe_offsets(&rule.selector) {
+                let Some(offset) = offset else {
+                    continue;
+                };
+                let Some((_, first)) = seen.iter().find(|(seen, _)| *seen == offset) else {
+                    seen.push((offset, rule));
+                    continue;
+                };
+                let message = if std::ptr::eq(*first, rule) {
+                    format!(
+                        "keyframe selector '{}' lists '{}' twice",
+                        rule.selector, part
+                    )
+                } else {
+                    format!(
+                        "keyframe '{}' repeats the offset of '{}' on line {}",
+                        part, first.selector, first.span.start.line
+                    )
+                };
+                errors.push(LintError::for_rule(
+                    rule,
+                    message,
+                    PrettystrictError::DuplicateKeyframe,
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+pub fn keyframe_important(rule: &Rule) -> Vec<LintError> {
+    if keyframes_at_rule(rule).is_none() {
+        return Vec::new();
+    }
+
+    rule.declaration
+        .iter()
+        .filter(|declaration| declaration.important)
+        .map(|declaration| {
+            LintError::new(
+                rule,
+                declaration,
+                format!(
+                    "{} is ignored inside keyframes because of !important",
+                    declaration.name
+                ),
+                PrettystrictError::KeyframeImportant,
+            )
+        })
+        .collect()
+}
+
+pub fn keyframe_animatable(rule: &Rule) -> Vec<LintError> {
+    if keyframes_at_rule(rule).is_none() {
+        return Vec::new();
+    }
+
+    rule.declaration
+        .iter()
+        .filter(|declaration| {
+            !declaration.is_custom() && !is_animatable(declaration.unprefixed_name())
+        })
+        .map(|declaration| {
+            LintError::new(
+                rule,
+                declaration,
+                format!(
+                    "{} cannot be animated and is ignored inside keyframes",
+                    declaration.name
+                ),
+                PrettystrictError::NotAnimatable(declaration.name.clone()),
+            )
+        })
+        .collect()
+}
+
+/// Reports properties animated by some keyframes of a block but missing from
+/// its `from` or `to` keyframe. An endpoint the block leaves out entirely
+/// takes every property from the element, so only written ones count, and
+/// `!important` declarations are ignored like browsers ignore them.
+pub fn missing_endpoint_properties(rules: &[Rule]) -> Vec<LintError> {
+    let mut errors = Vec::new();
+
+    for block in keyframe_blocks(rules) {
+        let mut animated: Vec<&str> = Vec::new();
+        for declaration in block.iter().flat_map(|rule| &rule.declaration) {
+            let name = declaration.name.as_str();
+            if !declaration.important
+                && name != "animation-timing-function"
+                && name != "animation-composition"
+                && is_animatable(declaration.unprefixed_name())
+                && !animated.contains(&name)
+            {
+                animated.push(name);
+            }
+        }
+
+        for (endpoint, label) in [(0.0, "from"), (100.0, "to")] {
+            let frames: Vec<&Rule> = block
+                .iter()
+                .copied()
+                .filter(|rule| {
+                    keyframe_offsets(&rule.selector)
+                        .iter()
+                        .any(|(_, offset)| *offset == Some(endpoint))
+                })
+                .collect();
+            let Some(first) = frames.first() else {
+                continue;
+            };
+
+            for name in &animated {
+                if frames
+                    .iter()
+                    .flat_map(|rule| &rule.declaration)
+                    .any(|d| !d.important && d.name == *name)
+                {
+                    continue;
+                }
+                errors.push(LintError {
+                    property: name.to_string(),
+                    ..LintError::for_rule(
+                        first,
+                        format!(
+                            "{} is animated by other keyframes but missing from the '{}' keyframe",
+                            name, label
+                        ),
+                        PrettystrictError::MissingEndpointProperty(name.to_string()),
+                    )
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+pub struct NoUnusedKeyframes;
+
+impl LintRule for NoUnusedKeyframes {
+    fn id(&self) -> &'static str {
+        "no-unused-keyframes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow @keyframes that no animation refers to"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        unused_keyframes(rules)
+    }
+}
+
+pub struct NoUndefinedKeyframes;
+
+impl LintRule for NoUndefinedKeyframes {
+    fn id(&self) -> &'static str {
+        "no-undefined-keyframes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow animation-name values without a matching @keyframes"
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        undefined_keyframes(rules)
+    }
+}
+
+pub struct KeyframeSelectorInRange;
+
+impl LintRule for KeyframeSelectorInRange {
+    fn id(&self) -> &'static str {
+        "keyframe-selector-in-range"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow keyframe selectors outside 0% to 100%"
+    }
+
+    fn check(&self, rule: &Rule, _context: &Context) -> Vec<LintError> {
+        keyframe_selector_range(rule)
+    }
+}
+
+pub struct NoDuplicateKeyframeOffsets;
+
+impl LintRule for NoDuplicateKeyframeOffsets {
+    fn id(&self) -> &'static str {
+        "no-duplicate-keyframe-offsets"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow keyframes that repeat an offset within one @keyframes"
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        duplicate_keyframe_offsets(rules)
+    }
+}
+
+pub struct KeyframeDeclarationNoImportant;
+
+impl LintRule for KeyframeDeclarationNoImportant {
+    fn id(&self) -> &'static str {
+        "keyframe-declaration-no-important"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow !important inside keyframes, where browsers ignore the declaration"
+    }
+
+    fn check(&self, rule: &Rule, _context: &Context) -> Vec<LintError> {
+        keyframe_important(rule)
+    }
+}
+
+pub struct KeyframePropertyAnimatable;
+
+impl LintRule for KeyframePropertyAnimatable {
+    fn id(&self) -> &'static str {
+        "keyframe-property-animatable"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow properties that cannot be animated inside keyframes"
+    }
+
+    fn check(&self, rule: &Rule, _context: &Context) -> Vec<LintError> {
+        keyframe_animatable(rule)
+    }
+}
+
+pub struct KeyframesNoMissingEndpointProperties;
+
+impl LintRule for KeyframesNoMissingEndpointProperties {
+    fn id(&self) -> &'static str {
+        "keyframes-no-missing-endpoint-properties"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require the from and to keyframes to set every animated property"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        missing_endpoint_properties(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{lint, reported};
+
+    #[test]
+    fn reports_keyframes_no_animation_refers_to() {
+        let source = "@keyframes fade {\n  from { opacity: 0; }\n}\n\
+            @keyframes spin {\n  to { rotate: 1turn; }\n}\n.a { animation: 1s ease spin; }\n";
+        assert_eq!(
+            lint(NoUnusedKeyframes, source),
+            reported("no-unused-keyframes", &[2])
+        );
+    }
+
+    #[test]
+    fn counts_quoted_animation_names_as_uses() {
+        let source =
+            "@keyframes fade {\n  from { opacity: 0; }\n}\n.a { animation-name: \"fade\"; }\n";
+        assert!(lint(NoUnusedKeyframes, source).is_empty());
+    }
+
+    #[test]
+    fn reports_animation_names_without_keyframes() {
+        let source = "@keyframes fade {\n  from { opacity: 0; }\n}\n\
+            .a { animation-name: fade, spin; }\n.b { animation-name: none; }\n";
+        assert_eq!(
+            lint(NoUndefinedKeyframes, source),
+            reported("no-undefined-keyframes", &[4])
+        );
+    }
+
+    #[test]
+    fn reads_keyframe_offsets() {
+        assert_eq!(
+            keyframe_offsets("FROM, 50%, to"),
+            [
+                ("FROM", Some(0.0)),
+                ("50%", Some(50.0)),
+                ("to", Some(100.0))
+            ]
+        );
+        assert_eq!(keyframe_offsets("entry 10%"), [("entry 10%", None)]);
+    }
+
+    #[test]
+    fn reports_offsets_outside_the_animation() {
+        let source = "@keyframes a {\n  from { opacity: 0; }\n  -10% { opacity: 0; }\n  \
+            50%, 120% { opacity: 1; }\n}\n.a { opacity: 0; }\n";
+        assert_eq!(
+            lint(KeyframeSelectorInRange, source),
+            reported("keyframe-selector-in-range", &[3, 4])
+        );
+    }
+
+    #[test]
+    fn reports_repeated_offsets_within_a_block() {
+        let source = "@keyframes a {\n  from { opacity: 0; }\n  0%, 50% { opacity: 1; }\n  \
+            to, 100% { opacity: 1; }\n}\n@keyframes b {\n  from { opacity: 0; }\n}\n";
+        assert_eq!(
+            lint(NoDuplicateKeyframeOffsets, source),
+            reported("no-duplicate-keyframe-offsets", &[3, 4])
+        );
+    }
+
+    #[test]
+    fn reports_important_declarations() {
+        let source = "@keyframes a {\n  from { opacity: 0; }\n  to {\n    opacity: 1 !important;\n  }\n}\n\
+            .a { opacity: 1 !important; }\n";
+        assert_eq!(
+            lint(KeyframeDeclarationNoImportant, source),
+            reported("keyframe-declaration-no-important", &[4])
+        );
+    }
+
+    #[test]
+    fn reports_properties_that_cannot_be_animated() {
+        let source = "@keyframes a {\n  from {\n    opacity: 0;\n    animation-delay: 1s;\n    \
+            animation-timing-function: ease;\n    --x: 1;\n    direction: rtl;\n  }\n}\n";
+        assert_eq!(
+            lint(KeyframePropertyAnimatable, source),
+            reported("keyframe-property-animatable", &[4, 7])
+        );
+    }
+
+    #[test]
+    fn reports_properties_missing_from_written_endpoints() {
+        let source = "@keyframes a {\n  from { opacity: 0; }\n  50% { opacity: 1; color: red; }\n  \
+            to { opacity: 0; }\n}\n@keyframes b {\n  50% { opacity: 1; }\n  to { opacity: 0; }\n}\n";
+        assert_eq!(
+            lint(KeyframesNoMissingEndpointProperties, source),
+            reported("keyframes-no-missing-endpoint-properties", &[2, 4])
+        );
+    }
+
+    #[test]
+    fn ignores_important_declarations_at_the_endpoints() {
+        let source = "@keyframes a {\n  from { opacity: 0; }\n  \
+            to { opacity: 1; transform: none !important; }\n}\n";
+        assert!(lint(KeyframesNoMissingEndpointProperties, source).is_empty());
+        let source = "@keyframes a {\n  from { opacity: 0; color: red !important; }\n  \
+            to { opacity: 1; color: blue; }\n}\n";
+        assert_eq!(
+            lint(KeyframesNoMissingEndpointProperties, source),
+            reported("keyframes-no-missing-endpoint-properties", &[2])
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use super::check_property::{AtRuleKind, Rule};
+use super::duplicate_selector::is_style_rule;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{Context, LintError, LintRule};
+
+/// Pixels per `em`/`rem` in media queries, which use the initial font size.
+const PX_PER_EM: f32 = 16.0;
+
+/// The viewport widths, in pixels, at which a rule applies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WidthRange {
+    min: f32,
+    max: f32,
+}
+
+impl WidthRange {
+    const ALL: WidthRange = WidthRange {
+        min: f32::NEG_INFINITY,
+        max: f32::INFINITY,
+    };
+
+    fn contains(&self, other: &WidthRange) -> bool {
+        self.min <= other.min && self.max >= other.max
+    }
+
+    fn intersect(self, other: WidthRange) -> WidthRange {
+        WidthRange {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        }
+    }
+}
+
+/// The widths `rule` applies at, or `None` when it sits in an at-rule other
+/// than a `@media` block built only from width conditions.
+fn width_range(rule: &Rule) -> Option<WidthRange> {
+    let mut range = WidthRange::ALL;
+    for at_rule in &rule.at_rule {
+        let AtRuleKind::Media { query } = &at_rule.kind else {
+            return None;
+        };
+        range = range.intersect(parse_query(query)?);
+    }
+    Some(range)
+}
+
+fn parse_query(query: &str) -> Option<WidthRange> {
+    // A query list matches when any of its queries does.
+    if query.contains(',') {
+        return None;
+    }
+
+    let mut range = WidthRange::ALL;
+    for part in query.split(" and ") {
+        let part = part.trim();
+        if matches!(part, "all" | "screen" | "only screen") {
+            continue;
+        }
+        let feature = part.strip_prefix('(')?.strip_suffix(')')?;
+        range = range.intersect(parse_feature(feature)?);
+    }
+    Some(range)
+}
+
+/// Parses `min-width: X`, `max-width: X` and the range forms lightningcss
+/// prints them as, such as `width >= X` and `X <= width < Y`. Strict and
+/// inclusive bounds are treated alike.
+fn parse_feature(feature: &str) -> Option<WidthRange> {
+    if let Some((name, value)) = feature.split_once(':') {
+        let value = parse_length(value.trim())?;
+        return match name.trim() {
+            "min-width" => Some(WidthRange { min: value, ..WidthRange::ALL }),
+            "max-width" => Some(WidthRange { max: value, ..WidthRange::ALL }),
+            _ => None,
+        };
+    }
+
+    let tokens: Vec<&str> = feature.split_whitespace().collect();
+    let at_least = |op: &str| matches!(op, ">=" | ">");
+    let at_most = |op: &str| matches!(op, "<=" | "<");
+    match tokens.as_slice() {
+        ["width", op, value] if at_least(op) => Some(WidthRange {
+            min: parse_length(value)?,
+            ..WidthRange::ALL
+        }),
+        ["width", op, value] if at_most(op) => Some(WidthRange {
+            max: parse_length(value)?,
+            ..WidthRange::ALL
+        }),
+        [value, op, "width"] if at_most(op) => Some(WidthRange {
+            min: parse_length(value)?,
+            ..WidthRange::ALL
+        }),
+        [value, op, "width"] if at_least(op) => Some(WidthRange {
+            max: parse_length(value)?,
+            ..WidthRange::ALL
+        }),
+        [min, low, "width", high, max] if at_most(low) && at_most(high) => Some(WidthRange {
+            min: parse_length(min)?,
+            max: parse_length(max)?,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_length(value: &str) -> Option<f32> {
+    if value == "0" {
+        return Some(0.0);
+    }
+    if let Some(px) = value.strip_suffix("px") {
+        return px.parse().ok();
+    }
+    let em = value.strip_suffix("rem").or_else(|| value.strip_suffix("em"))?;
+    em.parse::<f32>().ok().map(|em| em * PX_PER_EM)
+}
+
+/// Reports declarations in `@media` blocks that a later rule for the same
+/// selector always overrides, because it applies at every width the earlier
+/// block does.
+pub fn media_conflicts(rules: &[Rule]) -> Vec<LintError> {
+    let candidates: Vec<(&Rule, WidthRange)> = rules
+        .iter()
+        .filter(|rule| is_style_rule(rule))
+        .filter_map(|rule| Some((rule, width_range(rule)?)))
+        .collect();
+
+    let mut errors = Vec::new();
+    for (index, (rule, range)) in candidates.iter().enumerate() {
+        if rule.at_rule.is_empty() {
+            continue;
+        }
+        let later_rules: Vec<&Rule> = candidates[index + 1..]
+            .iter()
+            .filter(|(other, other_range)| {
+                other.selector == rule.selector
+                    && other.context() != rule.context()
+                    && other_range.contains(range)
+            })
+            .map(|(other, _)| *other)
+            .collect();
+
+        for declaration in &rule.declaration {
+            let overriding = later_rules.iter().find_map(|other| {
+                other
+                    .declaration
+                    .iter()
+                    .find(|d| {
+                        d.name == declaration.name
+                            && d.value != declaration.value
+                            // A normal declaration never overrides an
+                            // important one, however late it comes.
+                            && (d.important || !declaration.important)
+                    })
+                    .map(|d| (other, d))
+            });
+            let Some((other, overriding)) = overriding else {
+                continue;
+            };
+
+            let scope = if other.at_rule.is_empty() {
+                "outside any @media block".to_string()
+            } else {
+                let at_rules: Vec<String> =
+                    other.at_rule.iter().map(|a| a.kind.to_string()).collect();
+                format!("under {}", at_rules.join(" "))
+            };
+            errors.push(LintError {
+                fix: Some(format!(
+                    "move this block after line {} or narrow its query",
+                    other.span.start.line
+                )),
+                ..LintError::new(
+                    rule,
+                    declaration,
+                    format!(
+                        "'{}' is always overridden by '{}' at line {} {}",
+                        declaration.name,
+                        overriding.raw_value,
+                        overriding.span.start.line,
+                        scope
+                    ),
+                    PrettystrictError::MediaConflict,
+                )
+            });
+        }
+    }
+
+    errors
+}
+
+pub struct NoConflictingMedia;
+
+impl LintRule for NoConflictingMedia {
+    fn id(&self) -> &'static str {
+        "no-conflicting-media"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disallow @media declarations that a later rule always overrides"
+    }
+
+    fn check_stylesheet(&self, rules: &[Rule], _context: &Context) -> Vec<LintError> {
+        media_conflicts(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{lint, reported};
+
+    #[test]
+    fn reports_a_media_block_overridden_by_a_later_rule() {
+        let source = "@media (min-width: 640px) {\n  .a { color: red; }\n}\n.a { color: blue; }\n";
+        assert_eq!(
+            lint(NoConflictingMedia, source),
+            reported("no-conflicting-media", &[2])
+        );
+    }
+
+    #[test]
+    fn allows_a_later_rule_that_covers_fewer_widths() {
+        let source = "@media (min-width: 640px) {\n  .a { color: red; }\n}\n\
+            @media (min-width: 800px) {\n  .a { color: blue; }\n}\n";
+        assert!(lint(NoConflictingMedia, source).is_empty());
+    }
+
+    #[test]
+    fn converts_em_to_pixels() {
+        // 40em is 640px, so the later block applies wherever the first does.
+        let source = "@media (min-width: 50em) {\n  .a { color: red; }\n}\n\
+            @media (min-width: 640px) {\n  .a { color: blue; }\n}\n";
+        assert_eq!(
+            lint(NoConflictingMedia, source),
+            reported("no-conflicting-media", &[2])
+        );
+        let source = "@media (min-width: 30em) {\n  .a { color: red; }\n}\n\
+            @media (min-width: 640px) {\n  .a { color: blue; }\n}\n";
+        assert!(lint(NoConflictingMedia, source).is_empty());
+    }
+
+    #[test]
+    fn reads_range_syntax_and_max_width() {
+        let source = "@media (width >= 400px) and (max-width: 600px) {\n  .a { color: red; }\n}\n\
+            @media (max-width: 1000px) {\n  .a { color: blue; }\n}\n";
+        assert_eq!(
+            lint(NoConflictingMedia, source),
+            reported("no-conflicting-media", &[2])
+        );
+    }
+
+    #[test]
+    fn ignores_queries_it_cannot_compare() {
+        let source = "@media print {\n  .a { color: red; }\n}\n.a { color: blue; }\n\
+            @media (min-width: 1px), (orientation: portrait) {\n  .b { color: red; }\n}\n.b { color: blue; }\n";
+        assert!(lint(NoConflictingMedia, source).is_empty());
+    }
+
+    #[test]
+    fn keeps_important_declarations() {
+        let source = "@media (min-width: 40em) {\n  .b { margin: 1px !important; }\n}\n.b { margin: 2px; }\n";
+        assert!(lint(NoConflictingMedia, source).is_empty());
+    }
+}
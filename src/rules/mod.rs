@@ -1,5 +1,10 @@
 pub mod check_property;
 pub mod check_value;
 pub mod duplicate_declaration;
+pub mod duplicate_selector;
+pub mod font_face;
+pub mod keyframes;
+pub mod media_conflict;
+pub mod nesting;
 pub mod unit_check;
 
@@ -0,0 +1,90 @@
+use super::check_property::Rule;
+use crate::config::Severity;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{Context, LintError, LintRule, OptionKind, RuleOption};
+
+/// The deepest nesting allowed when the config does not set `max`.
+const DEFAULT_MAX_DEPTH: usize = 3;
+
+pub fn nesting_depth(rule: &Rule, max: usize) -> Vec<LintError> {
+    if rule.nesting_depth <= max {
+        return Vec::new();
+    }
+
+    vec![LintError::for_rule(
+        rule,
+        format!(
+            "'{}' is nested {} levels deep, more than the limit of {}",
+            rule.selector, rule.nesting_depth, max
+        ),
+        PrettystrictError::NestingDepth,
+    )]
+}
+
+pub struct MaxNestingDepth;
+
+impl LintRule for MaxNestingDepth {
+    fn id(&self) -> &'static str {
+        "max-nesting-depth"
+    }
+
+    fn description(&self) -> &'static str {
+        "Limit how deeply rules are nested inside style rules"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max",
+            kind: OptionKind::Count,
+        }]
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        let max = context
+            .settings
+            .option(self.id(), "max")
+            .unwrap_or(DEFAULT_MAX_DEPTH);
+        nesting_depth(rule, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{lint, lint_with, reported};
+
+    const NESTED: &str =
+        ".a {\n  .b {\n    .c {\n      .d {\n        .e { color: red; }\n      }\n    }\n  }\n}\n";
+
+    #[test]
+    fn allows_three_levels_by_default() {
+        assert_eq!(
+            lint(MaxNestingDepth, NESTED),
+            reported("max-nesting-depth", &[5])
+        );
+    }
+
+    #[test]
+    fn reads_the_limit_from_the_max_option() {
+        let config = "[rules]\nmax-nesting-depth = { max = 1 }\n";
+        assert_eq!(
+            lint_with(MaxNestingDepth, config, NESTED),
+            reported("max-nesting-depth", &[3, 4, 5])
+        );
+    }
+
+    #[test]
+    fn counts_at_rules_nested_in_style_rules() {
+        let config = "[rules]\nmax-nesting-depth = { max = 1 }\n";
+        let source = ".a {\n  @media print {\n    .b { color: red; }\n  }\n}\n\
+            @media print {\n  .c { color: red; }\n}\n";
+        assert_eq!(
+            lint_with(MaxNestingDepth, config, source),
+            reported("max-nesting-depth", &[3])
+        );
+    }
+}
@@ -1,23 +1,30 @@
 use crate::error::PrettystrictError;
-use crate::lint_rules::LintError;
+use crate::lint_rules::{Context, LintError, LintRule, OptionKind, RuleOption};
 use crate::rules::check_property::Rule;
 use crate::rules::check_value::{ValueList, ValueRule};
 use lazy_static::lazy_static;
 use regex::Regex;
 
-pub fn unit_check(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
+pub fn unit_check(
+    rule: &Rule,
+    known_values: &ValueList,
+    allowed_units: Option<&[String]>,
+) -> Vec<LintError> {
     lazy_static! {
         static ref UNIT_RE: Regex = Regex::new(r"(?i)^-?\d*\.?\d+\s*([a-z%]+)$").unwrap();
     }
 
     let mut errors = Vec::new();
+    if rule.declares_descriptors() {
+        return errors;
+    }
 
-    for decl in &rule.declaration {
+    for decl in rule.declaration.iter().filter(|d| !d.is_custom()) {
         let prop = decl.name.as_str();
-        let value = decl.value.trim();
+        let value = decl.value.trim().to_ascii_lowercase();
 
         let unit_opt = UNIT_RE
-            .captures(value)
+            .captures(&value)
             .and_then(|caps| caps.get(1))
             .map(|m| m.as_str());
 
@@ -28,39 +35,51 @@ pub fn unit_check(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
 
         let unit = unit_opt.unwrap();
 
+        if let Some(allowed) = allowed_units
+            && !allowed.iter().any(|u| u.eq_ignore_ascii_case(unit))
+        {
+            errors.push(LintError::new(
+                rule,
+                decl,
+                format!("Unit '{}' is not in the allowed unit list", unit),
+                PrettystrictError::WrongUnitDeclared,
+            ));
+            continue;
+        }
+
         match known_values.properties.get(prop) {
             Some(ValueRule::UnitRange { units, .. }) => {
                 if !units.contains(&unit.to_string()) {
-                    errors.push(LintError {
-                        selector: rule.selector.clone(),
-                        property: decl.name.clone(),
-                        message: format!("Unit '{}' is not allowed for '{}'", unit, prop),
-                        kind: PrettystrictError::WrongUnitDeclared,
-                    });
+                    errors.push(LintError::new(
+                        rule,
+                        decl,
+                        format!("Unit '{}' is not allowed for '{}'", unit, prop),
+                        PrettystrictError::WrongUnitDeclared,
+                    ));
                 }
             }
             Some(ValueRule::AllowedValues { .. }) => {
                 // Allowed value properties (e.g. display, text-align) shouldn't have units
-                errors.push(LintError {
-                    selector: rule.selector.clone(),
-                    property: decl.name.clone(),
-                    message: format!(
+                errors.push(LintError::new(
+                    rule,
+                    decl,
+                    format!(
                         "Unexpected unit '{}' for keyword-only property '{}'",
                         unit, prop
                     ),
-                    kind: PrettystrictError::WrongUnitDeclared,
-                });
+                    PrettystrictError::WrongUnitDeclared,
+                ));
             }
             None => {
-                errors.push(LintError {
-                    selector: rule.selector.clone(),
-                    property: decl.name.clone(),
-                    message: format!(
+                errors.push(LintError::new(
+                    rule,
+                    decl,
+                    format!(
                         "Unknown property '{}' — no unit validation rule found",
                         prop
                     ),
-                    kind: PrettystrictError::UnknownProperty(prop.to_string()),
-                });
+                    PrettystrictError::UnknownProperty(prop.to_string()),
+                ));
             }
             Some(&ValueRule::KeywordGroup { .. }) => {
                 eprintln!("invalid input")
@@ -70,3 +89,65 @@ pub fn unit_check(rule: &Rule, known_values: &ValueList) -> Vec<LintError> {
 
     errors
 }
+
+pub struct UnitAllowed;
+
+impl LintRule for UnitAllowed {
+    fn id(&self) -> &'static str {
+        "unit-allowed"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require units that are valid for the property"
+    }
+
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "units",
+            kind: OptionKind::StringList,
+        }]
+    }
+
+    fn check(&self, rule: &Rule, context: &Context) -> Vec<LintError> {
+        let units: Option<Vec<String>> = context.settings.option(self.id(), "units");
+        unit_check(rule, context.known_values, units.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_css::parse_css;
+    use crate::rules::check_value::load_known_values;
+
+    fn messages(source: &str, allowed_units: Option<&[String]>) -> Vec<String> {
+        let known_values = load_known_values().unwrap();
+        let rules = parse_css(source).expect("test CSS parses");
+        unit_check(&rules[0], &known_values, allowed_units)
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_units_in_any_case() {
+        assert!(messages(".a { left: 1EM; top: 2Px; }", None).is_empty());
+    }
+
+    #[test]
+    fn reports_units_outside_the_allowed_list() {
+        let allowed = ["px".to_string()];
+        assert_eq!(
+            messages(".a { left: 1EM; top: 2PX; }", Some(&allowed)),
+            ["Unit 'em' is not in the allowed unit list"]
+        );
+    }
+
+    #[test]
+    fn reports_units_the_property_does_not_take() {
+        assert_eq!(
+            messages(".a { left: 1vh; }", None),
+            ["Unit 'vh' is not allowed for 'left'"]
+        );
+    }
+}
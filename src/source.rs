@@ -0,0 +1,365 @@
+//provides source positions for diagnostics
+
+use serde::Serialize;
+use std::ops::Range;
+
+/// A 1-based line/column position, plus the byte offset it was computed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+/// A declaration found while scanning a block: `name` plus the byte range of
+/// the whole `name: value` text.
+#[derive(Debug, Clone)]
+pub struct ScannedDeclaration {
+    pub name: String,
+    pub range: Range<usize>,
+    /// The byte range of the value, without any `!important`.
+    pub value: Range<usize>,
+    /// Whether the value ends in `!important`.
+    pub important: bool,
+}
+
+/// A `prelude { ... }` found nested inside a block (keyframes, nested rules).
+#[derive(Debug, Clone)]
+pub struct ScannedBlock {
+    pub prelude: Range<usize>,
+    pub block: Block,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Block {
+    pub open: usize,
+    pub close: usize,
+    pub declarations: Vec<ScannedDeclaration>,
+    pub nested: Vec<ScannedBlock>,
+}
+
+impl Block {
+    /// Hands out declaration ranges in source order, so repeated names
+    /// resolve to successive occurrences.  Important and normal declarations
+    /// are matched separately, as the parser keeps them in separate lists.
+    pub fn cursor(&self) -> DeclarationCursor<'_> {
+        DeclarationCursor {
+            declarations: &self.declarations,
+            used: vec![false; self.declarations.len()],
+        }
+    }
+}
+
+pub struct DeclarationCursor<'a> {
+    declarations: &'a [ScannedDeclaration],
+    used: Vec<bool>,
+}
+
+impl<'a> DeclarationCursor<'a> {
+    pub fn take(&mut self, name: &str, important: bool) -> Option<&'a ScannedDeclaration> {
+        let index = self.declarations.iter().enumerate().position(|(i, d)| {
+            !self.used[i] && d.important == important && d.name.eq_ignore_ascii_case(name)
+        })?;
+        self.used[index] = true;
+        Some(&self.declarations[index])
+    }
+}
+
+pub struct Source<'a> {
+    pub text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Source { text, line_starts }
+    }
+
+    pub fn location(&self, offset: usize) -> Location {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let column = self.text[line_start..offset].chars().count() + 1;
+        Location {
+            line: line + 1,
+            column,
+            offset,
+        }
+    }
+
+    pub fn span(&self, range: Range<usize>) -> Span {
+        Span {
+            start: self.location(range.start),
+            end: self.location(range.end),
+        }
+    }
+
+    /// Converts a lightningcss position (0-based line, 1-based UTF-16 column)
+    /// into a byte offset.
+    pub fn offset_of(&self, line: u32, column: u32) -> usize {
+        let Some(&line_start) = self.line_starts.get(line as usize) else {
+            return self.text.len();
+        };
+        let mut units = column.saturating_sub(1) as usize;
+        let mut offset = line_start;
+        for ch in self.text[line_start..].chars() {
+            if units == 0 || ch == '\n' {
+                break;
+            }
+            units = units.saturating_sub(ch.len_utf16());
+            offset += ch.len_utf8();
+        }
+        offset
+    }
+
+    /// Finds the `{` that opens the block of the rule starting at `from`.
+    pub fn find_block_start(&self, from: usize) -> Option<usize> {
+        let end = self.prelude_end(from);
+        (self.text.as_bytes().get(end) == Some(&b'{')).then_some(end)
+    }
+
+    /// Finds the end of the prelude of the rule starting at `from`: the `{`
+    /// opening its block, the `;` ending a statement, or the `}` closing the
+    /// enclosing block.
+    pub fn prelude_end(&self, from: usize) -> usize {
+        let bytes = self.text.as_bytes();
+        let mut i = from;
+        let mut depth = 0usize;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(self.text, i),
+                b'"' | b'\'' => i = skip_string(self.text, i),
+                b'(' | b'[' => {
+                    depth += 1;
+                    i += 1;
+                }
+                b')' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    i += 1;
+                }
+                b'{' | b';' | b'}' if depth == 0 => return i,
+                _ => i += 1,
+            }
+        }
+        bytes.len()
+    }
+
+    /// Scans the block opened by the `{` at `open`, recording its top-level
+    /// declarations and nested blocks.
+    pub fn scan_block(&self, open: usize) -> Block {
+        let bytes = self.text.as_bytes();
+        let mut block = Block {
+            open,
+            close: self.text.len(),
+            ..Block::default()
+        };
+        let mut i = open + 1;
+
+        loop {
+            i = self.skip_trivia(i);
+            if i >= bytes.len() {
+                return block;
+            }
+            if bytes[i] == b'}' {
+                block.close = i;
+                return block;
+            }
+            if bytes[i] == b';' {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            let mut colon = None;
+            let mut depth = 0usize;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(self.text, i),
+                    b'"' | b'\'' => i = skip_string(self.text, i),
+                    b'(' | b'[' => {
+                        depth += 1;
+                        i += 1;
+                    }
+                    b')' | b']' => {
+                        depth = depth.saturating_sub(1);
+                        i += 1;
+                    }
+                    b':' if depth == 0 && colon.is_none() => {
+                        colon = Some(i);
+                        i += 1;
+                    }
+                    b';' | b'{' | b'}' if depth == 0 => break,
+                    _ => i += 1,
+                }
+            }
+
+            if bytes.get(i) == Some(&b'{') {
+                let nested = self.scan_block(i);
+                i = nested.close + 1;
+                block.nested.push(ScannedBlock {
+                    prelude: self.trim(start..nested.open),
+                    block: nested,
+                });
+                continue;
+            }
+
+            if let Some(colon) = colon {
+                let range = self.trim(start..i);
+                let value = self.trim(colon + 1..range.end);
+                let (text, important) = split_important(&self.text[value.clone()]);
+                block.declarations.push(ScannedDeclaration {
+                    name: self.text[self.trim(start..colon)].to_string(),
+                    value: value.start..value.start + text.len(),
+                    range,
+                    important,
+                });
+            }
+        }
+    }
+
+    /// The byte ranges of every comment, including the `/*` and `*/`.
+    pub fn comments(&self) -> Vec<Range<usize>> {
+        let bytes = self.text.as_bytes();
+        let mut comments = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    let end = skip_comment(self.text, i);
+                    comments.push(i..end);
+                    i = end;
+                }
+                b'"' | b'\'' => i = skip_string(self.text, i),
+                _ => i += 1,
+            }
+        }
+        comments
+    }
+
+    /// The byte range of the 1-based `line`, without its line break.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let Some(&start) = self.line_starts.get(line.wrapping_sub(1)) else {
+            return self.text.len()..self.text.len();
+        };
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.text.len(), |&next| next - 1);
+        start..end
+    }
+
+    fn skip_trivia(&self, mut i: usize) -> usize {
+        let bytes = self.text.as_bytes();
+        while i < bytes.len() {
+            if bytes[i].is_ascii_whitespace() {
+                i += 1;
+            } else if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'*') {
+                i = skip_comment(self.text, i);
+            } else {
+                break;
+            }
+        }
+        i
+    }
+
+    /// Narrows `range` to exclude surrounding whitespace and comments.
+    pub fn trim(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.skip_trivia(range.start).min(range.end);
+        let mut end = range.end;
+        loop {
+            let text = self.text[start..end].trim_end();
+            end = start + text.len();
+            if text.ends_with("*/") {
+                match text.rfind("/*") {
+                    Some(open) => end = start + open,
+                    None => break,
+                }
+            } else {
+                break;
+            }
+        }
+        start..end
+    }
+}
+
+/// The offset just past the comment starting at `i`.
+pub fn skip_comment(text: &str, i: usize) -> usize {
+    match text[i + 2..].find("*/") {
+        Some(end) => i + 2 + end + 2,
+        None => text.len(),
+    }
+}
+
+/// The offset just past the string whose quote is at `i`; an unterminated
+/// string ends at the line break.
+pub fn skip_string(text: &str, i: usize) -> usize {
+    let bytes = text.as_bytes();
+    let quote = bytes[i];
+    let mut j = i + 1;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            b'\n' => return j,
+            c if c == quote => return j + 1,
+            _ => j += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Splits `text` at commas outside parentheses, brackets, strings and
+/// escapes.
+pub fn split_top_level_commas(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    let mut depth = 0usize;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_comment(text, i),
+            b'"' | b'\'' => i = skip_string(text, i),
+            b'(' | b'[' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' | b']' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                i += 1;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+/// Splits a trailing `!important`, which may be spaced out or written in any
+/// case, off a declaration value.
+fn split_important(value: &str) -> (&str, bool) {
+    let value = value.trim_end();
+    let Some(split) = value.len().checked_sub("important".len()) else {
+        return (value, false);
+    };
+    if value.is_char_boundary(split) && value[split..].eq_ignore_ascii_case("important") {
+        let rest = value[..split].trim_end();
+        if let Some(rest) = rest.strip_suffix('!') {
+            return (rest.trim_end(), true);
+        }
+    }
+    (value, false)
+}
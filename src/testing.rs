@@ -0,0 +1,57 @@
+//provides helpers for linting small stylesheets in unit tests
+
+use crate::check_file::Linter;
+use crate::lint_rules::{LintRule, RuleRegistry};
+use crate::rules::check_property::load_known_props;
+use crate::rules::check_value::load_known_values;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Numbers the config files tests write, as tests run in parallel.
+static CONFIGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Lints `source` with `rule` as the only registered rule, under the config
+/// file contents `config`, and returns the rule id and line of each
+/// diagnostic in source order.
+pub fn lint_with(
+    rule: impl LintRule + 'static,
+    config: &str,
+    source: &str,
+) -> Vec<(String, usize)> {
+    let path = std::env::temp_dir().join(format!(
+        "prettystrict-test-{}-{}.toml",
+        std::process::id(),
+        CONFIGS.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&path, config).expect("test config is written");
+
+    let mut registry = RuleRegistry::empty();
+    registry.register(rule);
+    let linter = Linter::new(
+        load_known_props().unwrap(),
+        load_known_values().unwrap(),
+        registry,
+        Some(&path.to_string_lossy()),
+    );
+    std::fs::remove_file(&path).ok();
+
+    let mut errors = linter
+        .expect("test config loads")
+        .check_source(Path::new("test.css"), source.to_string())
+        .errors;
+    errors.sort_by_key(|error| (error.span.start.line, error.span.start.column));
+    errors
+        .into_iter()
+        .map(|error| (error.rule, error.span.start.line))
+        .collect()
+}
+
+/// Lints `source` with `rule` alone at its default settings.
+pub fn lint(rule: impl LintRule + 'static, source: &str) -> Vec<(String, usize)> {
+    lint_with(rule, "", source)
+}
+
+/// The diagnostics `lint` returns when `rule` reports each of `lines`.
+pub fn reported(id: &str, lines: &[usize]) -> Vec<(String, usize)> {
+    lines.iter().map(|&line| (id.to_string(), line)).collect()
+}
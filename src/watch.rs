@@ -0,0 +1,176 @@
+use crate::check_file::{FileReport, Linter};
+use crate::collect_files::{IGNORE_FILE, collect_files, collect_present_files, watch_roots};
+use crate::config::CONFIG_FILES;
+use crate::error::PrettystrictError;
+use crate::lint_rules::{LintError, RuleRegistry};
+use crate::report::Output;
+use notify_debouncer_mini::new_debouncer;
+use notify_debouncer_mini::notify::{RecursiveMode, Watcher};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+/// Bursts of saves closer together than this are linted once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Lints `files`, then re-lints changed stylesheets and redraws the report
+/// until the process is interrupted. Editing a config or ignore file re-reads
+/// it and re-lints every stylesheet.
+pub fn watch(
+    files: &[String],
+    ignore: &[String],
+    output: &Output,
+    linter: &mut Linter,
+) -> Result<(), LintError> {
+    let mut reports: BTreeMap<PathBuf, FileReport> = collect_files(files, ignore)?
+        .into_iter()
+        .map(|path| (path.clone(), linter.check_path(&path)))
+        .collect();
+    redraw(output, &reports, linter.registry())?;
+
+    // Reading a file is reported like writing it, so events only count when
+    // they change a path's modification time; that also keeps the reads
+    // made while linting from triggering another round.
+    let mut stamps: HashMap<PathBuf, Option<SystemTime>> = HashMap::new();
+    for path in reports.keys().cloned().chain(linter.config_files()) {
+        let path = canonical(&path);
+        stamps.insert(path.clone(), modified_time(&path));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(DEBOUNCE, tx).map_err(PrettystrictError::from)?;
+    let mut watched = Vec::new();
+    for (root, recursive) in watch_roots(files) {
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        debouncer
+            .watcher()
+            .watch(&root, mode)
+            .map_err(PrettystrictError::from)?;
+        watched.extend(root.canonicalize().ok().map(|root| (root, recursive)));
+    }
+    watch_config_dirs(debouncer.watcher(), linter, &mut watched)?;
+
+    for result in rx {
+        let events = result.map_err(PrettystrictError::from)?;
+        let changed: HashSet<PathBuf> = events
+            .into_iter()
+            .map(|event| canonical(&event.path))
+            .filter(|path| {
+                let time = modified_time(path);
+                stamps.insert(path.clone(), time) != Some(time)
+            })
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+
+        let config_files: HashSet<PathBuf> = linter
+            .config_files()
+            .iter()
+            .map(|path| canonical(path))
+            .collect();
+        let config_changed = changed
+            .iter()
+            .any(|path| is_config_file(path, &config_files));
+        if config_changed {
+            linter.reload_configs();
+        }
+
+        // Re-collect so created, deleted and newly ignored files are picked up.
+        let current = collect_present_files(files, ignore)?;
+        let before = reports.len();
+        reports.retain(|path, _| current.contains(path));
+
+        let mut dirty = reports.len() != before;
+        for path in current {
+            let modified = path
+                .canonicalize()
+                .is_ok_and(|canonical| changed.contains(&canonical));
+            if modified || config_changed || !reports.contains_key(&path) {
+                let report = linter.check_path(&path);
+                reports.insert(path, report);
+                dirty = true;
+            }
+        }
+
+        if dirty {
+            redraw(output, &reports, linter.registry())?;
+        }
+        watch_config_dirs(debouncer.watcher(), linter, &mut watched)?;
+    }
+
+    Ok(())
+}
+
+/// Starts watching the directory of each config file in use that no watch
+/// covers yet; watching the directory rather than the file itself keeps
+/// working when an editor saves by renaming over it. `watched` holds the
+/// canonical directories watched so far and whether they are recursive.
+fn watch_config_dirs(
+    watcher: &mut dyn Watcher,
+    linter: &Linter,
+    watched: &mut Vec<(PathBuf, bool)>,
+) -> Result<(), LintError> {
+    for file in linter.config_files() {
+        let Some(dir) = file.parent().and_then(|dir| dir.canonicalize().ok()) else {
+            continue;
+        };
+        let covered = watched.iter().any(|(root, recursive)| {
+            if *recursive {
+                dir.starts_with(root)
+            } else {
+                dir == *root
+            }
+        });
+        if !covered {
+            watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(PrettystrictError::from)?;
+            watched.push((dir, false));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` is a config file in use, or one whose appearance or
+/// removal changes which config or ignore rules apply.
+fn is_config_file(path: &Path, config_files: &HashSet<PathBuf>) -> bool {
+    let is_named = path.file_name().is_some_and(|name| {
+        name == IGNORE_FILE || CONFIG_FILES.iter().any(|config| name == *config)
+    });
+    is_named || config_files.contains(&canonical(path))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// `path` resolved through symlinks, or as given once it no longer exists.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn redraw(
+    output: &Output,
+    reports: &BTreeMap<PathBuf, FileReport>,
+    registry: &RuleRegistry,
+) -> Result<(), LintError> {
+    let reports: Vec<&FileReport> = reports.values().collect();
+
+    if output.prints_text() {
+        // Clear the screen and move the cursor home before printing.
+        print!("\x1b[2J\x1b[H");
+    }
+    output.emit(&reports, registry)?;
+    if output.prints_text() {
+        println!("\nWatching for changes...");
+    }
+    Ok(())
+}